@@ -5,10 +5,15 @@
 #[macro_use]
 extern crate alloc;
 
-use alloc::{string::String, vec::Vec};
+use alloc::{collections::BinaryHeap, string::String, vec::Vec};
+use core::cmp::Reverse;
 
 use stylus_sdk::{
-    alloy_primitives::{address, Address, U256}, alloy_sol_types::sol, console, prelude::*, storage::StorageType
+    abi::Bytes,
+    alloy_primitives::{address, b256, uint, Address, B256, U256, U8},
+    alloy_sol_types::{sol, sol_data, Eip712Domain, SolCall, SolStruct, SolType},
+    prelude::{calls::context::Call, *},
+    storage::StorageType,
 };
 
 // Error and event definitions
@@ -41,15 +46,68 @@ sol! {
     error InvalidFrequency();
     #[derive(Debug)]
     error NotSupportedToken();
+    #[derive(Debug)]
+    error ExceedsSurplus();
+    #[derive(Debug)]
+    error TimelockNotReady();
+    #[derive(Debug)]
+    error NothingQueued();
+    #[derive(Debug)]
+    error PaymentNotPending();
+    #[derive(Debug)]
+    error PaymentNotDue();
+    #[derive(Debug)]
+    error AccountSuspended();
+    #[derive(Debug)]
+    error ScheduleEnded();
+    #[derive(Debug)]
+    error InvalidSignature();
+    #[derive(Debug)]
+    error IntentExpired();
+    #[derive(Debug)]
+    error InvalidNonce();
+    #[derive(Debug)]
+    error DuplicateReference();
+    #[derive(Debug)]
+    error DestinationNotAllowed();
 
     event UserRegistered(address indexed user, string name, string country);
-    event PaymentSent(address indexed sender, address indexed recipient, uint256 amount, address token, uint256 paymentType);
+    event PaymentSent(address indexed sender, address indexed recipient, uint256 amount, address token, uint256 paymentType, bytes32 reference);
     event BeneficiaryAdded(address indexed user, address indexed beneficiary, string name, uint256 amount, address token, uint256 frequency);
     event BeneficiaryUpdated(address indexed user, address indexed beneficiary, uint256 amount, uint256 frequency);
+    event PaymentScheduleSet(address indexed user, uint256 indexed beneficiaryIndex, uint256 intervalSeconds, uint256 startTime, uint256 endTime, uint256 maxExecutions);
+    event PaymentScheduleCompleted(address indexed user, uint256 indexed beneficiaryIndex, uint256 executions);
+    event PaymentIntervalBoundsUpdated(uint256 minInterval, uint256 maxInterval);
+    event PaymentAnchorSet(address indexed user, uint256 indexed beneficiaryIndex, uint8 day, uint8 month);
     event BeneficiaryRemoved(address indexed user, address indexed beneficiary);
-    event AutoPaymentExecuted(address indexed sender, address indexed beneficiary, uint256 amount, address token, uint256 executionId);
-    event BalanceDeposited(address indexed user, address token, uint256 amount);
-    event BalanceWithdrawn(address indexed user, address token, uint256 amount);
+    event AutoPaymentExecuted(address indexed sender, address indexed beneficiary, uint256 amount, address token, uint256 executionId, uint256 periods, address keeper, uint256 keeperTip);
+    event BatchExecuted(address indexed keeper, uint256 succeeded, uint256 failed);
+    event CatchUpPolicySet(address indexed user, uint256 indexed beneficiaryIndex, uint8 policy, uint256 maxPeriods);
+    event KeeperTipUpdated(address indexed user, address indexed token, uint256 maxTip);
+    event BalanceDeposited(address indexed user, address indexed funder, address token, uint256 amount);
+    event BalanceWithdrawn(address indexed user, address token, uint256 amount, address destination);
+    event WithdrawalAllowlistUpdated(address indexed user, bool enabled, uint256 effectiveAt);
+    event WithdrawalDestinationAdded(address indexed user, address indexed destination, uint256 activeAt);
+    event WithdrawalDestinationRemoved(address indexed user, address indexed destination);
+    event SplitPaymentSent(uint256 indexed groupId, address indexed sender, address token, uint256 totalAmount, uint256 firstPaymentId, uint256 recipientCount);
+    event PaymentScheduled(uint256 indexed paymentId, address indexed sender, address indexed recipient, uint256 amount, address token, uint256 executeAfter);
+    event ScheduledPaymentCancelled(uint256 indexed paymentId, address indexed sender, uint256 amount);
+    event PaymentParked(address indexed recipient, address indexed token, address indexed sender, uint256 amount);
+    event PaymentClaimed(address indexed recipient, address indexed token, uint256 amount);
+    event InternalTransferFeeCharged(uint256 indexed paymentId, address indexed token, uint256 fee);
+    event FeesClaimed(address indexed treasury, address indexed token, uint256 amount);
+    event OwnershipTransferStarted(address indexed previousOwner, address indexed newOwner);
+    event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+    event GovernanceChangeQueued(uint256 indexed changeId, uint8 kind, uint256 value, address target, uint256 eta);
+    event GovernanceChangeExecuted(uint256 indexed changeId);
+    event GovernanceChangeCancelled(uint256 indexed changeId);
+    event RoleGranted(bytes32 indexed role, address indexed account, address indexed sender);
+    event RoleRevoked(bytes32 indexed role, address indexed account, address indexed sender);
+    event UserStatusChanged(address indexed user, bool active);
+    event EmergencyWithdrawalQueued(address indexed token, uint256 amount, uint256 eta);
+    event EmergencyWithdrawalCancelled(address indexed token, uint256 amount);
+    event EmergencyWithdrawal(address indexed token, address to, uint256 amount, uint256 shortfall);
+    event SignedIntentExecuted(address indexed signer, address indexed relayer, uint8 kind, uint256 nonce, address feeToken, uint256 relayerFee);
 }
 
 #[derive(SolidityError, Debug)]
@@ -68,15 +126,218 @@ pub enum RemittanceErrors {
     NotRegistered(NotRegistered),
     BeneficiaryNotFound(BeneficiaryNotFound),
     InvalidFrequency(InvalidFrequency),
+    ExceedsSurplus(ExceedsSurplus),
+    TimelockNotReady(TimelockNotReady),
+    NothingQueued(NothingQueued),
+    PaymentNotPending(PaymentNotPending),
+    PaymentNotDue(PaymentNotDue),
+    AccountSuspended(AccountSuspended),
+    ScheduleEnded(ScheduleEnded),
+    InvalidSignature(InvalidSignature),
+    IntentExpired(IntentExpired),
+    InvalidNonce(InvalidNonce),
+    DuplicateReference(DuplicateReference),
+    DestinationNotAllowed(DestinationNotAllowed),
+}
+
+/// Batch status code for an item that executed successfully.
+pub const BATCH_STATUS_OK: u8 = 0;
+
+impl RemittanceErrors {
+    /// Stable numeric code for batch results: the variant's position in this
+    /// enum, counting from 1 so that `BATCH_STATUS_OK` (0) means success.
+    pub fn code(&self) -> u8 {
+        match self {
+            RemittanceErrors::Unauthorized(_) => 1,
+            RemittanceErrors::InvalidConfiguration(_) => 2,
+            RemittanceErrors::UserAlreadyRegistered(_) => 3,
+            RemittanceErrors::InsufficientBalance(_) => 4,
+            RemittanceErrors::TransferFailed(_) => 5,
+            RemittanceErrors::InvalidRecipients(_) => 6,
+            RemittanceErrors::ExceedsLimit(_) => 7,
+            RemittanceErrors::ContractPaused(_) => 8,
+            RemittanceErrors::InvalidAmount(_) => 9,
+            RemittanceErrors::NotSupportedToken(_) => 10,
+            RemittanceErrors::FrequencyNotMet(_) => 11,
+            RemittanceErrors::NotRegistered(_) => 12,
+            RemittanceErrors::BeneficiaryNotFound(_) => 13,
+            RemittanceErrors::InvalidFrequency(_) => 14,
+            RemittanceErrors::ExceedsSurplus(_) => 15,
+            RemittanceErrors::TimelockNotReady(_) => 16,
+            RemittanceErrors::NothingQueued(_) => 17,
+            RemittanceErrors::PaymentNotPending(_) => 18,
+            RemittanceErrors::PaymentNotDue(_) => 19,
+            RemittanceErrors::AccountSuspended(_) => 20,
+            RemittanceErrors::ScheduleEnded(_) => 21,
+            RemittanceErrors::InvalidSignature(_) => 22,
+            RemittanceErrors::IntentExpired(_) => 23,
+            RemittanceErrors::InvalidNonce(_) => 24,
+            RemittanceErrors::DuplicateReference(_) => 25,
+            RemittanceErrors::DestinationNotAllowed(_) => 26,
+        }
+    }
 }
 
-// ERC20 interface
-sol_interface! {
+// Role identifiers, `keccak256("<NAME>")` as in OpenZeppelin's AccessControl
+pub const PAUSER_ROLE: B256 = b256!("65d7a28e3265b37a6474929f336521b332c1681b933f6cb9f3376673440d862a");
+pub const FEE_MANAGER_ROLE: B256 = b256!("6c0757dc3e6b28b2580c03fd9e96c274acf4f99d91fbec9b418fa1d70604ff1c");
+pub const TOKEN_MANAGER_ROLE: B256 = b256!("74f7a545c65c11839a48d7453738b30c295408df2d944516167556759ddc6d06");
+pub const LIMIT_MANAGER_ROLE: B256 = b256!("9d4608ca7098f32b4a58db3fe321284edffb95a174c82d9968f09dc583f7abba");
+pub const COMPLIANCE_ROLE: B256 = b256!("442a94f1a1fac79af32856af2a64f63648cfa2ef3b98610a5bb7cbec4cee6985");
+pub const TREASURY_ADMIN_ROLE: B256 = b256!("4ba1c0b393f1850d2175b0f2d7c2d42c3f898b0037de3434dbcee26a67c6df66");
+
+const ALL_ROLES: [B256; 6] = [
+    PAUSER_ROLE,
+    FEE_MANAGER_ROLE,
+    TOKEN_MANAGER_ROLE,
+    LIMIT_MANAGER_ROLE,
+    COMPLIANCE_ROLE,
+    TREASURY_ADMIN_ROLE,
+];
+
+// Kinds of timelocked governance change
+const CHANGE_PLATFORM_FEE: u8 = 0;
+const CHANGE_TREASURY: u8 = 1;
+const CHANGE_REMOVE_TOKEN: u8 = 2;
+const CHANGE_GOVERNANCE_DELAY: u8 = 3;
+const CHANGE_INTERNAL_TRANSFER_FEE: u8 = 4;
+
+/// Governance delay set at deployment (2 days) and the most it can be raised to (30 days).
+const DEFAULT_GOVERNANCE_DELAY: u64 = 2 * 86400;
+const MAX_GOVERNANCE_DELAY: u64 = 30 * 86400;
+
+// Beneficiary frequencies that follow the calendar rather than a fixed number of days
+const FREQUENCY_MONTHLY: u64 = 30;
+const FREQUENCY_YEARLY: u64 = 365;
+
+// What an auto-payment execution does about periods missed since the last one:
+// pay only the latest, pay all of them, or pay at most the latest N
+pub const CATCH_UP_SKIP: u8 = 0;
+pub const CATCH_UP_ALL: u8 = 1;
+pub const CATCH_UP_MAX_N: u8 = 2;
+
+/// Default bounds for beneficiary schedule intervals (1 hour to 1 year), and the
+/// longest interval an admin may allow (10 years).
+const DEFAULT_MIN_PAYMENT_INTERVAL: u64 = 3600;
+const DEFAULT_MAX_PAYMENT_INTERVAL: u64 = 365 * 86400;
+const MAX_PAYMENT_INTERVAL: u64 = 10 * 365 * 86400;
+
+/// Most entries `execute_due` and `peek_due` handle per call, and how long a due
+/// entry that failed to execute waits before the queue offers it again.
+const MAX_DUE_ITEMS: u64 = 100;
+const DUE_RETRY_DELAY: u64 = 3600;
+
+/// Gas kept in hand before starting another batch item: a worst-case item
+/// (cold storage writes, fee, payout and tip transfers, events) plus the return.
+const BATCH_ITEM_GAS_RESERVE: u64 = 300_000;
+
+/// Most recipients one split payment can pay.
+const MAX_SPLIT_RECIPIENTS: usize = 20;
+
+/// Delay before a queued emergency withdrawal may dip into user funds (2 days).
+const EMERGENCY_WITHDRAW_DELAY: u64 = 2 * 86400;
+
+/// Cooling-off period before a newly allowlisted withdrawal destination can be
+/// used, or before switching the allowlist off takes effect (1 day).
+const WITHDRAWAL_DESTINATION_DELAY: u64 = 86400;
+
+// Kinds of signed intent accepted by `execute_signed`
+pub const INTENT_REGISTER_USER: u8 = 0;
+pub const INTENT_SEND_PAYMENT: u8 = 1;
+pub const INTENT_ADD_BENEFICIARY: u8 = 2;
+pub const INTENT_WITHDRAW: u8 = 3;
+
+/// EIP-712 domain name and version for signed intents.
+const EIP712_NAME: &str = "UniversalRemittance";
+const EIP712_VERSION: &str = "1";
+
+/// The `ecrecover` precompile, and half the secp256k1 group order: signatures with
+/// a larger `s` are malleable copies of a valid one and are rejected.
+const ECRECOVER: Address = address!("0000000000000000000000000000000000000001");
+const SECP256K1_HALF_ORDER: U256 = uint!(0x7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0_U256);
+
+/// Stands in for native ETH wherever a token address is expected. Pulls of it are
+/// paid with `msg_value` on the payable entry points; it must be added with
+/// `add_supported_token` like any other token.
+pub const NATIVE_TOKEN: Address = address!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+// ERC20 interface. Only the call encodings are used: tokens are called through
+// `call_token` rather than `sol_interface!` bindings, whose strict `bool` decoding
+// would reject tokens that return no data.
+sol! {
     interface IERC20 {
         function transfer(address to, uint256 amount) external returns (bool);
         function transferFrom(address from, address to, uint256 amount) external returns (bool);
         function balanceOf(address account) external view returns (uint256);
         function allowance(address owner, address spender) external view returns (uint256);
+        function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+    }
+}
+
+// ERC-1271 interface for smart-contract wallet signatures
+sol! {
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+    }
+}
+
+/// What `isValidSignature` returns for a signature the wallet accepts (its own selector).
+const ERC1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+// EIP-712 typed data for `execute_signed`. Each intent names its signer, the
+// fee the relayer takes from the signer's internal balance, and a nonce and
+// deadline so it can be used once and only for a limited time. `RegisterUser`
+// must carry a zero `relayerFee`: a new user has no balance to pay it from.
+sol! {
+    #[derive(Debug)]
+    struct RegisterUser {
+        address signer;
+        string name;
+        string country;
+        string phoneNumber;
+        address feeToken;
+        uint256 relayerFee;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    #[derive(Debug)]
+    struct SendPayment {
+        address signer;
+        address recipient;
+        uint256 amount;
+        address token;
+        string note;
+        address feeToken;
+        uint256 relayerFee;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    #[derive(Debug)]
+    struct AddBeneficiary {
+        address signer;
+        address beneficiary;
+        string name;
+        string relationship;
+        uint256 amount;
+        address token;
+        uint256 frequency;
+        address feeToken;
+        uint256 relayerFee;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    #[derive(Debug)]
+    struct Withdraw {
+        address signer;
+        address token;
+        uint256 amount;
+        address feeToken;
+        uint256 relayerFee;
+        uint256 nonce;
+        uint256 deadline;
     }
 }
 
@@ -99,10 +360,20 @@ sol_storage! {
         string relationship; // "family", "friend", "business", etc.
         uint256 amount;
         address token;
-        uint256 frequency; // 0=manual, 1=daily, 7=weekly, 30=monthly, 365=yearly
+        uint256 frequency; // Days between payments: 0=manual, 30=calendar monthly, 365=calendar yearly
         uint256 last_payment;
         bool is_active;
         uint256 total_sent;
+        uint8 anchor_day; // Day of month for monthly/yearly schedules, 0 = day of first payment
+        uint8 anchor_month; // Month for yearly schedules, 0 = month of first payment
+        uint256 interval_seconds; // Overrides `frequency` when non-zero
+        uint256 start_time; // 0 = payable straight away
+        uint256 end_time; // 0 = open-ended
+        uint256 max_executions; // 0 = unlimited
+        uint256 executions;
+        bool completed; // Deactivated by reaching `max_executions`
+        uint8 catch_up_policy; // CATCH_UP_SKIP, CATCH_UP_ALL or CATCH_UP_MAX_N
+        uint256 catch_up_max; // Periods paid at most under CATCH_UP_MAX_N
     }
     
     pub struct Payment {
@@ -111,17 +382,32 @@ sol_storage! {
         uint256 amount;
         address token;
         uint256 timestamp;
-        uint256 payment_type; // 0=manual, 1=auto, 2=scheduled
+        uint256 payment_type; // 0=manual, 1=auto, 2=scheduled, 3=internal transfer, 4=deposit for another user
         string note;
         bool completed;
+        uint256 execute_after; // Scheduled payments only
+        bool cancelled;
+        uint256 group_id; // Split payments only, shared by every leg
+        bytes32 reference; // Sender's idempotency key, zero if none
+    }
+
+    pub struct GovernanceChange {
+        uint8 kind; // 0=platform fee, 1=treasury, 2=remove token, 3=governance delay, 4=internal transfer fee
+        uint256 value;
+        address target;
+        uint256 eta;
+        bool executed;
+        bool cancelled;
     }
 
     #[entrypoint]
     pub struct UniversalRemittance {
         address owner;
+        address pending_owner;
         bool paused;
         address treasury;
         uint256 platform_fee_percent; // In basis points (50 = 0.5%)
+        uint256 internal_transfer_fee_percent; // In basis points, for `internal_transfer`
         uint256 payment_count;
         uint256 execution_count;
         
@@ -129,12 +415,17 @@ sol_storage! {
         mapping(address => UserProfile) users;
         mapping(address => bool) registered_users;
         
+        // Access control: role => account => granted
+        mapping(bytes32 => mapping(address => bool)) roles;
+        
         // Beneficiary management  
         mapping(address => mapping(uint256 => Beneficiary)) user_beneficiaries; // user => index => beneficiary
         mapping(address => uint256) beneficiary_counts; // user => count
         
         // Payment tracking
         mapping(uint256 => Payment) payments; // payment ID => payment
+        mapping(address => mapping(bytes32 => uint256)) payment_references; // sender => reference => payment ID + 1
+        uint256 split_group_count;
         
         // Supported tokens
         mapping(address => bool) supported_tokens;
@@ -142,6 +433,46 @@ sol_storage! {
         // Daily limits (optional, can be 0 for unlimited)
         mapping(address => uint256) daily_limits;
         mapping(address => mapping(uint256 => uint256)) daily_spent; // user => day => amount
+        
+        // Allowed range for beneficiary schedule intervals, in seconds
+        uint256 min_payment_interval;
+        uint256 max_payment_interval;
+        
+        // Sum of internal balances owed to users, per token
+        mapping(address => uint256) token_liabilities;
+        
+        // Active schedules as a binary min-heap keyed by next due time, stored column-wise
+        uint256[] due_times;
+        address[] due_users;
+        uint256[] due_indices;
+        mapping(address => mapping(uint256 => uint256)) due_positions; // user => index => heap position + 1
+        
+        // Most a user pays whoever executes one of their auto-payments: user => token => tip
+        mapping(address => mapping(address => uint256)) keeper_tips;
+        
+        // Payouts the recipient must pull after a push transfer failed
+        mapping(address => mapping(address => uint256)) claimable; // recipient => token => amount
+        
+        // Internal transfer fees the treasury has yet to collect: token => amount
+        mapping(address => uint256) accrued_fees;
+        
+        // Timelocked governance changes
+        uint256 governance_delay;
+        uint256 governance_change_count;
+        mapping(uint256 => GovernanceChange) governance_changes;
+        uint256[] pending_change_ids;
+        
+        // Queued emergency withdrawals that may exceed the surplus, per token
+        mapping(address => uint256) emergency_amounts;
+        mapping(address => uint256) emergency_etas;
+        
+        // Next nonce each user's signed intents must carry
+        mapping(address => uint256) intent_nonces;
+        
+        // Opt-in allowlists of payout destinations
+        mapping(address => bool) withdrawal_allowlist_enabled;
+        mapping(address => uint256) withdrawal_allowlist_disable_at; // 0 = no switch-off pending
+        mapping(address => mapping(address => uint256)) withdrawal_destinations; // user => destination => usable from, 0 = not listed
     }
 }
 
@@ -150,14 +481,25 @@ sol_storage! {
 impl UniversalRemittance {
     
     #[constructor]
-    pub fn constructor(&mut self, treasury: Address) -> Result<(), RemittanceErrors> {
+    pub fn constructor(&mut self, initial_owner: Address, treasury: Address) -> Result<(), RemittanceErrors> {
         if self.owner.get() != Address::ZERO {
             return Err(RemittanceErrors::Unauthorized(Unauthorized {}));
         }
+        // Explicit rather than `msg_sender`/`tx_origin`, which name the deployer
+        // contract or the EOA behind a factory or smart-contract wallet.
+        if initial_owner == Address::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
         
-        self.owner.set(self.vm().tx_origin());
+        self.owner.set(initial_owner);
+        for role in ALL_ROLES {
+            self.grant_role_internal(role, initial_owner);
+        }
         self.treasury.set(treasury);
         self.platform_fee_percent.set(U256::from(50)); // 0.5%
+        self.governance_delay.set(U256::from(DEFAULT_GOVERNANCE_DELAY));
+        self.min_payment_interval.set(U256::from(DEFAULT_MIN_PAYMENT_INTERVAL));
+        self.max_payment_interval.set(U256::from(DEFAULT_MAX_PAYMENT_INTERVAL));
         
         // Add common stablecoins
         let usdc_arbitrum = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
@@ -178,105 +520,199 @@ impl UniversalRemittance {
     ) -> Result<(), RemittanceErrors> {
         self.when_not_paused()?;
         let sender = self.vm().msg_sender();
-        
-        if self.registered_users.get(sender) {
-            return Err(RemittanceErrors::UserAlreadyRegistered(UserAlreadyRegistered {}));
+        self.register_user_internal(sender, name, country, phone_number)
+    }
+
+    // === BALANCE MANAGEMENT === //
+    
+    #[payable]
+    pub fn deposit_balance(&mut self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered()?;
+        let sender = self.vm().msg_sender();
+        self.deposit_internal(sender, sender, token, amount)
+    }
+    
+    /// Tops up a registered user's internal balance from the caller's wallet, e.g.
+    /// so a relative's own schedules keep running. The caller need not be registered;
+    /// the deposit is recorded as a payment from the caller to `user`.
+    #[payable]
+    pub fn deposit_for(&mut self, user: Address, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered_account(user)?;
+        let funder = self.vm().msg_sender();
+        self.deposit_internal(funder, user, token, amount)
+    }
+    
+    /// `deposit_balance` preceded by an EIP-2612 permit, so no separate approval
+    /// transaction is needed.
+    pub fn deposit_with_permit(
+        &mut self,
+        token: Address,
+        amount: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), RemittanceErrors> {
+        self.token_permit(token, amount, deadline, v, r, s);
+        self.deposit_balance(token, amount)
+    }
+    
+    pub fn withdraw_balance(&mut self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        let sender = self.vm().msg_sender();
+        self.withdraw_balance_internal(sender, token, amount, sender)
+    }
+    
+    /// Withdraws to another address, such as an exchange deposit address or a
+    /// cold wallet. With the allowlist on, `destination` must have been added
+    /// with `add_withdrawal_destination` and have cleared its cooling-off period.
+    pub fn withdraw_to(&mut self, token: Address, amount: U256, destination: Address) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        if destination == Address::ZERO {
+            return Err(RemittanceErrors::InvalidRecipients(InvalidRecipients {}));
         }
         
-        let block_timestamp = U256::from(self.vm().block_timestamp());
-        
-        let mut profile = self.users.setter(sender);
-        profile.name.set_str(&name);
-        profile.country.set_str(&country);
-        profile.phone_number.set_str(&phone_number);
-        profile.is_active.set(true);
-        profile.total_sent.set(U256::ZERO);
-        profile.total_received.set(U256::ZERO);
-        profile.registration_time.set(block_timestamp);
+        let sender = self.vm().msg_sender();
+        self.only_allowed_destination(sender, destination)?;
         
-        self.registered_users.setter(sender).set(true);
+        self.withdraw_balance_internal(sender, token, amount, destination)
+    }
+    
+    /// Turns the caller's destination allowlist on straight away, or off after
+    /// `WITHDRAWAL_DESTINATION_DELAY` so a hijacked account cannot simply drop it.
+    /// While it is on, every payout from the internal balance (withdrawals,
+    /// payments, internal transfers, schedules, beneficiaries and relayer fees)
+    /// must go to the caller or an allowlisted destination.
+    pub fn set_withdrawal_allowlist(&mut self, enabled: bool) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered()?;
         
-        log(self.vm(), UserRegistered {
+        let sender = self.vm().msg_sender();
+        let current_time = U256::from(self.vm().block_timestamp());
+        let effective_at = if enabled || !self.withdrawal_allowlist_active(sender) {
+            self.withdrawal_allowlist_enabled.setter(sender).set(enabled);
+            self.withdrawal_allowlist_disable_at.setter(sender).set(U256::ZERO);
+            current_time
+        } else {
+            let disable_at = current_time + U256::from(WITHDRAWAL_DESTINATION_DELAY);
+            self.withdrawal_allowlist_disable_at.setter(sender).set(disable_at);
+            disable_at
+        };
+        
+        log(self.vm(), WithdrawalAllowlistUpdated {
             user: sender,
-            name,
-            country,
+            enabled,
+            effectiveAt: effective_at,
         });
         
         Ok(())
     }
-
-    // === BALANCE MANAGEMENT === //
     
-    pub fn deposit_balance(&mut self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+    /// Allowlists a payout destination; it becomes usable once
+    /// `WITHDRAWAL_DESTINATION_DELAY` has passed.
+    pub fn add_withdrawal_destination(&mut self, destination: Address) -> Result<(), RemittanceErrors> {
         self.when_not_paused()?;
         self.only_registered()?;
-        if amount == U256::ZERO {
-            return Err(RemittanceErrors::InvalidAmount(InvalidAmount {}));
-        }
-        if !self.supported_tokens.get(token) {
-            return Err(RemittanceErrors::NotSupportedToken(NotSupportedToken {}));
+        if destination == Address::ZERO {
+            return Err(RemittanceErrors::InvalidRecipients(InvalidRecipients {}));
         }
         
         let sender = self.vm().msg_sender();
-        let contract_addr = self.vm().contract_address();
-        let token_contract = IERC20::new(token);
-
-        // Transfer tokens to contract
-        match token_contract.transfer_from(&mut *self, sender, contract_addr, amount) {
-            Ok(success) => {
-                if !success {
-                    return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                }
-            }
-            Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
+        if self.withdrawal_destinations.getter(sender).get(destination) != U256::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
+        let active_at = U256::from(self.vm().block_timestamp() + WITHDRAWAL_DESTINATION_DELAY);
+        self.withdrawal_destinations.setter(sender).setter(destination).set(active_at);
         
-        // Update internal balance
-        let mut user_profile = self.users.setter(sender);
-        let current_balance = user_profile.token_balances.get(token);
-        user_profile.token_balances.setter(token).set(current_balance + amount);
-        
-        log(self.vm(), BalanceDeposited {
+        log(self.vm(), WithdrawalDestinationAdded {
             user: sender,
-            token,
-            amount,
+            destination,
+            activeAt: active_at,
         });
         
         Ok(())
     }
     
-    pub fn withdraw_balance(&mut self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+    pub fn remove_withdrawal_destination(&mut self, destination: Address) -> Result<(), RemittanceErrors> {
         self.when_not_paused()?;
         self.only_registered()?;
         
-        if !self.supported_tokens.get(token) || amount == U256::ZERO {
+        let sender = self.vm().msg_sender();
+        if self.withdrawal_destinations.getter(sender).get(destination) == U256::ZERO {
             return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
+        self.withdrawal_destinations.setter(sender).setter(destination).set(U256::ZERO);
         
-        let sender = self.vm().msg_sender();
-        let mut user_profile = self.users.setter(sender);
-        let current_balance = user_profile.token_balances.get(token);
+        log(self.vm(), WithdrawalDestinationRemoved {
+            user: sender,
+            destination,
+        });
         
-        if current_balance < amount {
+        Ok(())
+    }
+    
+    /// Returns `(active, disable_at)` for the user's allowlist; `disable_at` is
+    /// non-zero while a switch-off is pending.
+    pub fn get_withdrawal_allowlist(&self, user: Address) -> (bool, U256) {
+        (self.withdrawal_allowlist_active(user), self.withdrawal_allowlist_disable_at.get(user))
+    }
+    
+    /// Returns when an allowlisted destination becomes usable, or 0 if it is not listed.
+    pub fn get_withdrawal_destination(&self, user: Address, destination: Address) -> U256 {
+        self.withdrawal_destinations.getter(user).get(destination)
+    }
+
+    /// Pulls payouts that were parked because a push transfer to the caller failed.
+    pub fn claim(&mut self, token: Address) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        
+        let recipient = self.vm().msg_sender();
+        let amount = self.claimable.getter(recipient).get(token);
+        if amount == U256::ZERO {
             return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
         }
         
-        // Update internal balance
-        user_profile.token_balances.setter(token).set(current_balance - amount);
+        self.check_liabilities(token, amount)?;
         
-        // Transfer tokens to user
-        let token_contract = IERC20::new(token);
-        match token_contract.transfer(&mut *self, sender, amount) {
-            Ok(success) => {
-                if !success {
-                    return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                }
-            }
-            Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
+        self.claimable.setter(recipient).setter(token).set(U256::ZERO);
+        self.decrease_liabilities(token, amount);
+        
+        self.token_transfer(token, recipient, amount)?;
+        
+        log(self.vm(), PaymentClaimed {
+            recipient,
+            token,
+            amount,
+        });
+        
+        Ok(())
+    }
+    
+    /// Sends the internal transfer fees accrued in `token` to the treasury. Only the
+    /// current treasury may call it, so fees accrued before a treasury change go to
+    /// the new one.
+    pub fn claim_fees(&mut self, token: Address) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        
+        let treasury_addr = self.treasury.get();
+        if self.vm().msg_sender() != treasury_addr {
+            return Err(RemittanceErrors::Unauthorized(Unauthorized {}));
+        }
+        let amount = self.accrued_fees.get(token);
+        if amount == U256::ZERO {
+            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
         }
+        self.check_liabilities(token, amount)?;
         
-        log(self.vm(), BalanceWithdrawn {
-            user: sender,
+        self.accrued_fees.setter(token).set(U256::ZERO);
+        self.decrease_liabilities(token, amount);
+        
+        self.token_transfer(token, treasury_addr, amount)?;
+        
+        log(self.vm(), FeesClaimed {
+            treasury: treasury_addr,
             token,
             amount,
         });
@@ -286,13 +722,63 @@ impl UniversalRemittance {
 
     // === PAYMENT FUNCTIONS === //
     
+    #[payable]
     pub fn send_payment(
         &mut self,
         recipient: Address,
         amount: U256,
         token: Address,
         note: String,
+        reference: B256, // Optional idempotency key; a repeat from the same sender is rejected
+    ) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        let sender = self.vm().msg_sender();
+        self.send_payment_internal(sender, recipient, amount, token, &note, reference)
+    }
+    
+    /// `send_payment` preceded by an EIP-2612 permit for `amount`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn send_payment_with_permit(
+        &mut self,
+        recipient: Address,
+        amount: U256,
+        token: Address,
+        note: String,
+        reference: B256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), RemittanceErrors> {
+        self.token_permit(token, amount, deadline, v, r, s);
+        self.send_payment(recipient, amount, token, note, reference)
+    }
+    
+    /// Same as `send_payment`, but debits the sender's deposited balance instead
+    /// of pulling from their wallet, so no ERC20 approval is needed.
+    pub fn send_payment_from_balance(
+        &mut self,
+        recipient: Address,
+        amount: U256,
+        token: Address,
+        note: String,
     ) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        let sender = self.vm().msg_sender();
+        self.send_payment_from_balance_internal(sender, recipient, amount, token, &note)
+    }
+
+    /// Moves `amount` of the caller's internal balance to another registered user's
+    /// without any token transfer. The internal transfer fee accrues in the fee
+    /// ledger for the treasury to collect with `claim_fees`, and the recipient
+    /// receives the rest. Returns the payment id.
+    pub fn internal_transfer(
+        &mut self,
+        to: Address,
+        token: Address,
+        amount: U256,
+        note: String,
+    ) -> Result<U256, RemittanceErrors> {
         self.when_not_paused()?;
         self.only_registered()?;
         
@@ -301,51 +787,31 @@ impl UniversalRemittance {
         }
         
         let sender = self.vm().msg_sender();
+        if to == sender {
+            return Err(RemittanceErrors::InvalidRecipients(InvalidRecipients {}));
+        }
+        self.only_registered_account(to)?;
+        self.only_allowed_destination(sender, to)?;
         
         // Check daily limit if set
         if !self.check_daily_limit(sender, amount) {
             return Err(RemittanceErrors::ExceedsLimit(ExceedsLimit {}));
         }
         
-        let token_contract = IERC20::new(token);
-        let contract_addr = self.vm().contract_address();
-        
-        // Transfer tokens to contract
-        match token_contract.transfer_from(&mut *self, sender, contract_addr, amount) {
-            Ok(success) => {
-                if !success {
-                    return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                }
-            }
-            Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
-        }
-        
-        // Calculate fee
-        let platform_fee = (amount * self.platform_fee_percent.get()) / U256::from(10000);
-        let net_amount = amount.checked_sub(platform_fee)
-            .ok_or(RemittanceErrors::InvalidAmount(InvalidAmount {}))?;
+        let fee = (amount * self.internal_transfer_fee_percent.get()) / U256::from(10000);
+        let net_amount = amount - fee;
         
-        // Send to recipient
-        match token_contract.transfer(&mut *self, recipient, net_amount) {
-            Ok(success) => {
-                if !success {
-                    return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                }
-            }
-            Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
+        // Move the balance; liabilities are unchanged since the fee stays owed to the treasury
+        let sender_balance = self.users.get(sender).token_balances.get(token);
+        if sender_balance < amount {
+            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
         }
-        
-        // Send fee to treasury
-        if platform_fee > U256::ZERO {
-            let treasury_addr = self.treasury.get();
-            match token_contract.transfer(&mut *self, treasury_addr, platform_fee) {
-                Ok(success) => {
-                    if !success {
-                        return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                    }
-                }
-                Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
-            }
+        self.users.setter(sender).token_balances.setter(token).set(sender_balance - amount);
+        let recipient_balance = self.users.get(to).token_balances.get(token);
+        self.users.setter(to).token_balances.setter(token).set(recipient_balance + net_amount);
+        if fee > U256::ZERO {
+            let accrued = self.accrued_fees.get(token);
+            self.accrued_fees.setter(token).set(accrued + fee);
         }
         
         // Record payment
@@ -354,11 +820,11 @@ impl UniversalRemittance {
         
         let mut payment = self.payments.setter(payment_id);
         payment.sender.set(sender);
-        payment.recipient.set(recipient);
+        payment.recipient.set(to);
         payment.amount.set(amount);
         payment.token.set(token);
         payment.timestamp.set(block_timestamp);
-        payment.payment_type.set(U256::ZERO); // Manual payment
+        payment.payment_type.set(U256::from(3)); // Internal transfer
         payment.note.set_str(&note);
         payment.completed.set(true);
         
@@ -368,79 +834,296 @@ impl UniversalRemittance {
         let mut sender_profile = self.users.setter(sender);
         let sender_total = sender_profile.total_sent.get();
         sender_profile.total_sent.set(sender_total + amount);
+        let mut recipient_profile = self.users.setter(to);
+        let recipient_total = recipient_profile.total_received.get();
+        recipient_profile.total_received.set(recipient_total + net_amount);
         
-        if self.registered_users.get(recipient) {
-            let mut recipient_profile = self.users.setter(recipient);
-            let recipient_total = recipient_profile.total_received.get();
-            recipient_profile.total_received.set(recipient_total + net_amount);
-        }
-        
-        // Update daily spent
         self.update_daily_spent(sender, amount);
         
         log(self.vm(), PaymentSent {
             sender,
-            recipient,
+            recipient: to,
             amount,
             token,
-            paymentType: U256::ZERO,
+            paymentType: U256::from(3),
+            reference: B256::ZERO,
         });
+        if fee > U256::ZERO {
+            log(self.vm(), InternalTransferFeeCharged {
+                paymentId: payment_id,
+                token,
+                fee,
+            });
+        }
         
-        Ok(())
+        Ok(payment_id)
     }
 
-    // === BENEFICIARY MANAGEMENT === //
-    
-    pub fn add_beneficiary(
+    /// Pays several recipients from one `transferFrom` of the total. The daily limit
+    /// applies to the total and the combined fee goes to the treasury in a single
+    /// transfer. Each leg is recorded as its own payment under a shared group id,
+    /// which is returned.
+    #[payable]
+    pub fn send_split_payment(
         &mut self,
-        beneficiary_address: Address,
-        name: String,
-        relationship: String,
+        recipients: Vec<Address>,
+        amounts: Vec<U256>,
+        token: Address,
+        note: String,
+    ) -> Result<U256, RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered()?;
+        
+        if !self.supported_tokens.get(token) {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        if recipients.is_empty()
+            || recipients.len() != amounts.len()
+            || recipients.len() > MAX_SPLIT_RECIPIENTS
+            || recipients.contains(&Address::ZERO)
+        {
+            return Err(RemittanceErrors::InvalidRecipients(InvalidRecipients {}));
+        }
+        
+        let mut total = U256::ZERO;
+        for amount in &amounts {
+            if *amount == U256::ZERO {
+                return Err(RemittanceErrors::InvalidAmount(InvalidAmount {}));
+            }
+            total = total.checked_add(*amount)
+                .ok_or(RemittanceErrors::InvalidAmount(InvalidAmount {}))?;
+        }
+        
+        let sender = self.vm().msg_sender();
+        
+        // The daily limit covers the whole split
+        if !self.check_daily_limit(sender, total) {
+            return Err(RemittanceErrors::ExceedsLimit(ExceedsLimit {}));
+        }
+        
+        let contract_addr = self.vm().contract_address();
+        self.token_transfer_from(token, sender, contract_addr, total)?;
+        
+        let group_id = self.split_group_count.get() + U256::from(1);
+        self.split_group_count.set(group_id);
+        let first_payment_id = self.payment_count.get();
+        
+        let fee_percent = self.platform_fee_percent.get();
+        let mut total_fee = U256::ZERO;
+        for (recipient, amount) in recipients.iter().zip(amounts.iter()) {
+            let platform_fee = (*amount * fee_percent) / U256::from(10000);
+            let net_amount = *amount - platform_fee;
+            total_fee += platform_fee;
+            
+            self.token_transfer(token, *recipient, net_amount)?;
+            self.record_manual_payment(sender, *recipient, *amount, net_amount, token, &note, group_id, B256::ZERO);
+        }
+        
+        if total_fee > U256::ZERO {
+            let treasury_addr = self.treasury.get();
+            self.token_transfer(token, treasury_addr, total_fee)?;
+        }
+        
+        let mut sender_profile = self.users.setter(sender);
+        let sender_total = sender_profile.total_sent.get();
+        sender_profile.total_sent.set(sender_total + total);
+        self.update_daily_spent(sender, total);
+        
+        log(self.vm(), SplitPaymentSent {
+            groupId: group_id,
+            sender,
+            token,
+            totalAmount: total,
+            firstPaymentId: first_payment_id,
+            recipientCount: U256::from(recipients.len()),
+        });
+        
+        Ok(group_id)
+    }
+
+    /// Reserves `amount` from the sender's internal balance for a one-time payment
+    /// that anyone may execute once `execute_after` has passed.
+    pub fn schedule_payment(
+        &mut self,
+        recipient: Address,
         amount: U256,
         token: Address,
-        frequency: U256, // 0=manual, 1=daily, 7=weekly, 30=monthly, 365=yearly
-    ) -> Result<(), RemittanceErrors> {
+        execute_after: U256,
+        note: String,
+    ) -> Result<U256, RemittanceErrors> {
         self.when_not_paused()?;
         self.only_registered()?;
         
         if !self.supported_tokens.get(token) || amount == U256::ZERO {
             return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
+        if recipient == Address::ZERO {
+            return Err(RemittanceErrors::InvalidRecipients(InvalidRecipients {}));
+        }
         
-        // Validate frequency
-        if frequency != U256::ZERO && frequency != U256::from(1) && frequency != U256::from(7) && 
-           frequency != U256::from(30) && frequency != U256::from(365) {
-            return Err(RemittanceErrors::InvalidFrequency(InvalidFrequency {}));
+        let block_timestamp = U256::from(self.vm().block_timestamp());
+        if execute_after <= block_timestamp {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
         
         let sender = self.vm().msg_sender();
-        let beneficiary_count = self.beneficiary_counts.get(sender);
+        self.only_allowed_destination(sender, recipient)?;
         
-        let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
-        let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_count);
-        beneficiary.beneficiary_address.set(beneficiary_address);
-        beneficiary.name.set_str(&name);
-        beneficiary.relationship.set_str(&relationship);
-        beneficiary.amount.set(amount);
-        beneficiary.token.set(token);
-        beneficiary.frequency.set(frequency);
-        beneficiary.last_payment.set(U256::ZERO);
-        beneficiary.is_active.set(true);
-        beneficiary.total_sent.set(U256::ZERO);
+        // Check daily limit if set
+        if !self.check_daily_limit(sender, amount) {
+            return Err(RemittanceErrors::ExceedsLimit(ExceedsLimit {}));
+        }
         
-        self.beneficiary_counts.setter(sender).set(beneficiary_count + U256::from(1));
+        // Reserve funds; they stay counted as liabilities until executed
+        let mut user_profile = self.users.setter(sender);
+        let current_balance = user_profile.token_balances.get(token);
+        if current_balance < amount {
+            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
+        }
+        user_profile.token_balances.setter(token).set(current_balance - amount);
         
-        log(self.vm(), BeneficiaryAdded {
-            user: sender,
-            beneficiary: beneficiary_address,
-            name,
+        // Record payment
+        let payment_id = self.payment_count.get();
+        
+        let mut payment = self.payments.setter(payment_id);
+        payment.sender.set(sender);
+        payment.recipient.set(recipient);
+        payment.amount.set(amount);
+        payment.token.set(token);
+        payment.timestamp.set(block_timestamp);
+        payment.payment_type.set(U256::from(2)); // Scheduled payment
+        payment.note.set_str(&note);
+        payment.completed.set(false);
+        payment.execute_after.set(execute_after);
+        
+        self.payment_count.set(payment_id + U256::from(1));
+        
+        // Update daily spent
+        self.update_daily_spent(sender, amount);
+        
+        log(self.vm(), PaymentScheduled {
+            paymentId: payment_id,
+            sender,
+            recipient,
             amount,
             token,
-            frequency,
+            executeAfter: execute_after,
+        });
+        
+        Ok(payment_id)
+    }
+    
+    pub fn execute_scheduled_payment(&mut self, payment_id: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_pending_scheduled(payment_id)?;
+        
+        let payment = self.payments.get(payment_id);
+        if U256::from(self.vm().block_timestamp()) < payment.execute_after.get() {
+            return Err(RemittanceErrors::PaymentNotDue(PaymentNotDue {}));
+        }
+        
+        let sender = payment.sender.get();
+        let recipient = payment.recipient.get();
+        let amount = payment.amount.get();
+        let token = payment.token.get();
+        if !self.users.get(sender).is_active.get() {
+            return Err(RemittanceErrors::AccountSuspended(AccountSuspended {}));
+        }
+        
+        // Calculate fee
+        let platform_fee = (amount * self.platform_fee_percent.get()) / U256::from(10000);
+        let net_amount = amount.checked_sub(platform_fee)
+            .ok_or(RemittanceErrors::InvalidAmount(InvalidAmount {}))?;
+        
+        self.check_liabilities(token, amount)?;
+        
+        self.payments.setter(payment_id).completed.set(true);
+        self.decrease_liabilities(token, amount);
+        
+        // Send fee to treasury
+        if platform_fee > U256::ZERO {
+            let treasury_addr = self.treasury.get();
+            self.token_transfer(token, treasury_addr, platform_fee)?;
+        }
+        
+        // Send to recipient
+        self.pay_or_park(sender, token, recipient, net_amount);
+        
+        // Update user stats
+        let mut sender_profile = self.users.setter(sender);
+        let sender_total = sender_profile.total_sent.get();
+        sender_profile.total_sent.set(sender_total + amount);
+        
+        if self.registered_users.get(recipient) {
+            let mut recipient_profile = self.users.setter(recipient);
+            let recipient_total = recipient_profile.total_received.get();
+            recipient_profile.total_received.set(recipient_total + net_amount);
+        }
+        
+        log(self.vm(), PaymentSent {
+            sender,
+            recipient,
+            amount,
+            token,
+            paymentType: U256::from(2),
+            reference: B256::ZERO,
+        });
+        
+        Ok(())
+    }
+    
+    /// Cancels a scheduled payment before it runs and returns the reserved funds
+    /// to the sender's internal balance.
+    pub fn cancel_scheduled_payment(&mut self, payment_id: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_pending_scheduled(payment_id)?;
+        
+        let sender = self.vm().msg_sender();
+        let payment = self.payments.get(payment_id);
+        if payment.sender.get() != sender {
+            return Err(RemittanceErrors::Unauthorized(Unauthorized {}));
+        }
+        let amount = payment.amount.get();
+        let token = payment.token.get();
+        let scheduled_day = payment.timestamp.get() / U256::from(86400);
+        
+        self.payments.setter(payment_id).cancelled.set(true);
+        
+        let mut user_profile = self.users.setter(sender);
+        let current_balance = user_profile.token_balances.get(token);
+        user_profile.token_balances.setter(token).set(current_balance + amount);
+        
+        // Give back the daily allowance if the payment is cancelled the day it was scheduled
+        let today = U256::from(self.vm().block_timestamp() / 86400);
+        if scheduled_day == today {
+            let spent = self.daily_spent.getter(sender).get(today);
+            self.daily_spent.setter(sender).setter(today).set(spent.saturating_sub(amount));
+        }
+        
+        log(self.vm(), ScheduledPaymentCancelled {
+            paymentId: payment_id,
+            sender,
+            amount,
         });
         
         Ok(())
     }
+
+    // === BENEFICIARY MANAGEMENT === //
+    
+    pub fn add_beneficiary(
+        &mut self,
+        beneficiary_address: Address,
+        name: String,
+        relationship: String,
+        amount: U256,
+        token: Address,
+        frequency: U256, // Days between payments: 0=manual, 30=calendar monthly, 365=calendar yearly
+    ) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        let sender = self.vm().msg_sender();
+        self.add_beneficiary_internal(sender, beneficiary_address, name, relationship, amount, token, frequency)
+    }
     
     pub fn update_beneficiary(
         &mut self,
@@ -458,11 +1141,7 @@ impl UniversalRemittance {
             return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
         }
         
-        // Validate frequency
-        if frequency != U256::ZERO && frequency != U256::from(1) && frequency != U256::from(7) && 
-           frequency != U256::from(30) && frequency != U256::from(365) {
-            return Err(RemittanceErrors::InvalidFrequency(InvalidFrequency {}));
-        }
+        self.validate_frequency(frequency)?;
         
         let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
         let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
@@ -472,9 +1151,17 @@ impl UniversalRemittance {
             return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
         }
         
+        // An anchor or custom interval only makes sense for the schedule it was set for
+        if beneficiary.frequency.get() != frequency {
+            beneficiary.anchor_day.set(U8::ZERO);
+            beneficiary.anchor_month.set(U8::ZERO);
+            beneficiary.interval_seconds.set(U256::ZERO);
+        }
         beneficiary.amount.set(amount);
         beneficiary.frequency.set(frequency);
         
+        self.sync_due_queue(sender, beneficiary_index);
+        
         log(self.vm(), BeneficiaryUpdated {
             user: sender,
             beneficiary: beneficiary_address,
@@ -485,10 +1172,32 @@ impl UniversalRemittance {
         Ok(())
     }
     
-    pub fn remove_beneficiary(&mut self, beneficiary_index: U256) -> Result<(), RemittanceErrors> {
+    /// Bounds a beneficiary's schedule. A non-zero `interval_seconds` replaces the
+    /// day-based frequency; zero for `start_time`, `end_time` or `max_executions`
+    /// leaves that bound open, except that a pinned calendar schedule that has not
+    /// paid yet starts now. The beneficiary deactivates after its last execution.
+    pub fn set_payment_schedule(
+        &mut self,
+        beneficiary_index: U256,
+        interval_seconds: U256,
+        start_time: U256,
+        end_time: U256,
+        max_executions: U256,
+    ) -> Result<(), RemittanceErrors> {
         self.when_not_paused()?;
         self.only_registered()?;
         
+        if interval_seconds != U256::ZERO
+            && (interval_seconds < self.min_payment_interval.get() || interval_seconds > self.max_payment_interval.get())
+        {
+            return Err(RemittanceErrors::InvalidFrequency(InvalidFrequency {}));
+        }
+        
+        let current_time = U256::from(self.vm().block_timestamp());
+        if end_time != U256::ZERO && (end_time <= start_time || end_time <= current_time) {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
         let sender = self.vm().msg_sender();
         let beneficiary_count = self.beneficiary_counts.get(sender);
         
@@ -498,166 +1207,517 @@ impl UniversalRemittance {
         
         let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
         let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
-        let beneficiary_address = beneficiary.beneficiary_address.get();
         
         if !beneficiary.is_active.get() {
             return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
         }
+        if max_executions != U256::ZERO && max_executions <= beneficiary.executions.get() {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
         
-        beneficiary.is_active.set(false);
+        // A pinned schedule that has not paid yet counts from now rather than from no start at all
+        let start_time = if start_time == U256::ZERO
+            && interval_seconds == U256::ZERO
+            && beneficiary.anchor_day.get() != U8::ZERO
+            && beneficiary.last_payment.get() == U256::ZERO
+        {
+            current_time
+        } else {
+            start_time
+        };
         
-        log(self.vm(), BeneficiaryRemoved {
+        beneficiary.interval_seconds.set(interval_seconds);
+        beneficiary.start_time.set(start_time);
+        beneficiary.end_time.set(end_time);
+        beneficiary.max_executions.set(max_executions);
+        
+        self.sync_due_queue(sender, beneficiary_index);
+        
+        log(self.vm(), PaymentScheduleSet {
             user: sender,
-            beneficiary: beneficiary_address,
+            beneficiaryIndex: beneficiary_index,
+            intervalSeconds: interval_seconds,
+            startTime: start_time,
+            endTime: end_time,
+            maxExecutions: max_executions,
         });
         
         Ok(())
     }
-
-    // === AUTO PAYMENT EXECUTION === //
     
-    pub fn execute_auto_payments(&mut self, user: Address, beneficiary_index: U256) -> Result<(), RemittanceErrors> {
+    /// Chooses how an execution handles periods missed since the last payment; see
+    /// `CATCH_UP_SKIP`, `CATCH_UP_ALL` and `CATCH_UP_MAX_N`. `max_periods` only
+    /// applies to `CATCH_UP_MAX_N` and must be at least 1 there.
+    pub fn set_catch_up_policy(
+        &mut self,
+        beneficiary_index: U256,
+        policy: u8,
+        max_periods: U256,
+    ) -> Result<(), RemittanceErrors> {
         self.when_not_paused()?;
-
-        // Get block timestamp before any mutable borrow
+        self.only_registered()?;
+        
+        let valid = match policy {
+            CATCH_UP_SKIP | CATCH_UP_ALL => max_periods == U256::ZERO,
+            CATCH_UP_MAX_N => max_periods != U256::ZERO,
+            _ => false,
+        };
+        if !valid {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        let sender = self.vm().msg_sender();
+        let beneficiary_count = self.beneficiary_counts.get(sender);
+        
+        if beneficiary_index >= beneficiary_count {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        
+        let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
+        let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
+        
+        if !beneficiary.is_active.get() {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        
+        beneficiary.catch_up_policy.set(U8::from(policy));
+        beneficiary.catch_up_max.set(max_periods);
+        
+        log(self.vm(), CatchUpPolicySet {
+            user: sender,
+            beneficiaryIndex: beneficiary_index,
+            policy,
+            maxPeriods: max_periods,
+        });
+        
+        Ok(())
+    }
+    
+    /// Pins a monthly schedule to a day of the month (1-31), or a yearly schedule
+    /// to a date. Days past the end of a short month fall on its last day. A schedule
+    /// that has not paid yet first pays on the next anchor date, counting from its
+    /// start time or, without one, from now.
+    pub fn set_payment_anchor(
+        &mut self,
+        beneficiary_index: U256,
+        day: u8,
+        month: u8,
+    ) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered()?;
+        
         let current_time = U256::from(self.vm().block_timestamp());
-
-        let beneficiary_count = self.beneficiary_counts.get(user);
+        let sender = self.vm().msg_sender();
+        let beneficiary_count = self.beneficiary_counts.get(sender);
+        
         if beneficiary_index >= beneficiary_count {
             return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
         }
-
-        let mut user_beneficiaries_setter = self.user_beneficiaries.setter(user);
-        let beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
-        if !beneficiary.is_active.get() || beneficiary.frequency.get() == U256::ZERO {
+        
+        let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
+        let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
+        
+        if !beneficiary.is_active.get() {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        
+        if beneficiary.interval_seconds.get() != U256::ZERO {
+            return Err(RemittanceErrors::InvalidFrequency(InvalidFrequency {}));
+        }
+        let valid = match u64::try_from(beneficiary.frequency.get()) {
+            Ok(FREQUENCY_MONTHLY) => month == 0 && (1..=31).contains(&day),
+            // Feb 29 is allowed and lands on Feb 28 in common years
+            Ok(FREQUENCY_YEARLY) => (1..=12).contains(&month) && day >= 1 && u32::from(day) <= days_in_month(2000, u32::from(month)),
+            _ => return Err(RemittanceErrors::InvalidFrequency(InvalidFrequency {})),
+        };
+        if !valid {
             return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
-
-        let last_payment = beneficiary.last_payment.get();
-        let frequency_seconds = beneficiary.frequency.get() * U256::from(86400); // Convert days to seconds
-
-        if last_payment > U256::ZERO && (current_time - last_payment) < frequency_seconds {
-            return Err(RemittanceErrors::FrequencyNotMet(FrequencyNotMet {}));
+        
+        beneficiary.anchor_day.set(U8::from(day));
+        beneficiary.anchor_month.set(U8::from(month));
+        if beneficiary.last_payment.get() == U256::ZERO && beneficiary.start_time.get() == U256::ZERO {
+            beneficiary.start_time.set(current_time);
         }
-
-        let amount = beneficiary.amount.get();
-        let token = beneficiary.token.get();
-        let beneficiary_address = beneficiary.beneficiary_address.get();
-
-        // Check user's internal balance
-        let user_profile = self.users.get(user);
-        let user_balance = user_profile.token_balances.get(token);
-
-        if user_balance < amount {
-            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
+        
+        self.sync_due_queue(sender, beneficiary_index);
+        
+        log(self.vm(), PaymentAnchorSet {
+            user: sender,
+            beneficiaryIndex: beneficiary_index,
+            day,
+            month,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn remove_beneficiary(&mut self, beneficiary_index: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered()?;
+        
+        let sender = self.vm().msg_sender();
+        let beneficiary_count = self.beneficiary_counts.get(sender);
+        
+        if beneficiary_index >= beneficiary_count {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
         }
-
-        // Calculate fee
-        let platform_fee = (amount * self.platform_fee_percent.get()) / U256::from(10000);
-        let net_amount = amount.checked_sub(platform_fee)
-            .ok_or(RemittanceErrors::InvalidAmount(InvalidAmount {}))?;
-
-        // Update user's internal balance
-        {
-            let mut user_profile_setter = self.users.setter(user);
-            user_profile_setter.token_balances.setter(token).set(user_balance - amount);
+        
+        let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
+        let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
+        let beneficiary_address = beneficiary.beneficiary_address.get();
+        
+        if !beneficiary.is_active.get() {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
         }
+        
+        beneficiary.is_active.set(false);
+        
+        self.sync_due_queue(sender, beneficiary_index);
+        
+        log(self.vm(), BeneficiaryRemoved {
+            user: sender,
+            beneficiary: beneficiary_address,
+        });
+        
+        Ok(())
+    }
 
-        // Transfer to beneficiary
-        let token_contract = IERC20::new(token);
-        let transfer_result = token_contract.transfer(&mut *self, beneficiary_address, net_amount);
-        match transfer_result {
-            Ok(success) => {
-                if !success {
-                    return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                }
+    // === AUTO PAYMENT EXECUTION === //
+    
+    pub fn execute_auto_payments(&mut self, user: Address, beneficiary_index: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.process_auto_payment(user, beneficiary_index)?;
+        Ok(())
+    }
+    
+    /// Executes up to `max_items` of the earliest due auto-payments from the on-chain
+    /// queue, stopping early when gas runs low, and returns how many succeeded. An
+    /// entry that fails is pushed back by `DUE_RETRY_DELAY` so it cannot block the
+    /// entries behind it.
+    pub fn execute_due(&mut self, max_items: U256) -> Result<U256, RemittanceErrors> {
+        self.when_not_paused()?;
+        
+        let current_time = U256::from(self.vm().block_timestamp());
+        let max_items = max_items.min(U256::from(MAX_DUE_ITEMS)).to::<u64>();
+        let mut executed = 0u64;
+        let mut failed = 0u64;
+        
+        for _ in 0..max_items {
+            if !self.has_gas_for_item() {
+                break;
             }
-            Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
-        }
-
-        // Send fee to treasury
-        if platform_fee > U256::ZERO {
-            let treasury_addr = self.treasury.get();
-            let fee_result = token_contract.transfer(&mut *self, treasury_addr, platform_fee);
-            match fee_result {
-                Ok(success) => {
-                    if !success {
-                        return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                    }
+            let Some(due) = self.due_times.get(0) else { break };
+            if due > current_time {
+                break;
+            }
+            let user = self.due_users.get(0).unwrap_or_default();
+            let beneficiary_index = self.due_indices.get(0).unwrap_or_default();
+            
+            if self.process_auto_payment(user, beneficiary_index).is_ok() {
+                executed += 1;
+                continue;
+            }
+            
+            // Drop or re-time the entry, then defer it if it is still due
+            failed += 1;
+            self.sync_due_queue(user, beneficiary_index);
+            let position = self.due_positions.getter(user).get(beneficiary_index);
+            if position != U256::ZERO {
+                let position = position.to::<usize>() - 1;
+                if self.due_times.get(position).unwrap_or_default() <= current_time {
+                    self.due_rekey(position, current_time + U256::from(DUE_RETRY_DELAY));
                 }
-                Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
             }
         }
-
-        // Re-borrow to update beneficiary
-        {
-            let mut user_beneficiaries_setter = self.user_beneficiaries.setter(user);
-            let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
-            beneficiary.last_payment.set(current_time);
-            let beneficiary_total = beneficiary.total_sent.get();
-            beneficiary.total_sent.set(beneficiary_total + amount);
-        }
-
-        // Update user stats
-        {
-            let mut user_profile_setter = self.users.setter(user);
-            let user_total = user_profile_setter.total_sent.get();
-            user_profile_setter.total_sent.set(user_total + amount);
-        }
-
-        // Update recipient stats if registered
-        if self.registered_users.get(beneficiary_address) {
-            let mut recipient_profile = self.users.setter(beneficiary_address);
-            let recipient_total = recipient_profile.total_received.get();
-            recipient_profile.total_received.set(recipient_total + net_amount);
+        
+        log(self.vm(), BatchExecuted {
+            keeper: self.vm().msg_sender(),
+            succeeded: U256::from(executed),
+            failed: U256::from(failed),
+        });
+        
+        Ok(U256::from(executed))
+    }
+    
+    /// Sets the tip paid to third parties that execute the caller's auto-payments
+    /// in `token`. Tips come out of the internal balance; zero disables them.
+    /// While the caller's allowlist is on, only allowlisted keepers are tipped.
+    pub fn set_keeper_tip(&mut self, token: Address, max_tip: U256) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        self.only_registered()?;
+        
+        if !self.supported_tokens.get(token) {
+            return Err(RemittanceErrors::NotSupportedToken(NotSupportedToken {}));
         }
-
-        // Record execution
-        let execution_id = self.execution_count.get();
-        self.execution_count.set(execution_id + U256::from(1));
-
-        log(self.vm(), AutoPaymentExecuted {
-            sender: user,
-            beneficiary: beneficiary_address,
-            amount,
+        
+        let sender = self.vm().msg_sender();
+        self.keeper_tips.setter(sender).setter(token).set(max_tip);
+        
+        log(self.vm(), KeeperTipUpdated {
+            user: sender,
             token,
-            executionId: execution_id,
+            maxTip: max_tip,
         });
+        
+        Ok(())
+    }
+    
+    pub fn get_keeper_tip(&self, user: Address, token: Address) -> U256 {
+        self.keeper_tips.getter(user).get(token)
+    }
 
+    // === SIGNED INTENTS === //
+    
+    /// Carries out an EIP-712 intent for its signer, so users without ETH can act
+    /// through a relayer. `intent` is `abi.encode(uint8 kind, bytes data)`, with
+    /// `data` the ABI-encoded `RegisterUser`, `SendPayment`, `AddBeneficiary` or
+    /// `Withdraw` struct that `kind` names. `signature` is the signer's 65-byte
+    /// `r || s || v` signature over its typed-data hash, or, when the signer is a
+    /// contract wallet, whatever its ERC-1271 `isValidSignature` accepts. Payments
+    /// and a non-zero relayer fee (paid to the caller) both come out of the
+    /// signer's internal balance, so no token approval is ever needed.
+    pub fn execute_signed(&mut self, intent: Bytes, signature: Bytes) -> Result<(), RemittanceErrors> {
+        self.when_not_paused()?;
+        
+        let malformed = |_| RemittanceErrors::InvalidConfiguration(InvalidConfiguration {});
+        let (kind, data) = <(sol_data::Uint<8>, sol_data::Bytes) as SolType>::abi_decode_params(&intent, true)
+            .map_err(malformed)?;
+        
+        let (signer, fee_token, relayer_fee, nonce) = match kind {
+            INTENT_REGISTER_USER => {
+                let intent = <RegisterUser as SolType>::abi_decode(&data, true).map_err(malformed)?;
+                if intent.relayerFee != U256::ZERO {
+                    return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+                }
+                self.use_intent(&intent, intent.signer, intent.nonce, intent.deadline, intent.relayerFee, &signature)?;
+                self.register_user_internal(intent.signer, intent.name, intent.country, intent.phoneNumber)?;
+                (intent.signer, intent.feeToken, intent.relayerFee, intent.nonce)
+            }
+            INTENT_SEND_PAYMENT => {
+                let intent = <SendPayment as SolType>::abi_decode(&data, true).map_err(malformed)?;
+                self.use_intent(&intent, intent.signer, intent.nonce, intent.deadline, intent.relayerFee, &signature)?;
+                self.send_payment_from_balance_internal(intent.signer, intent.recipient, intent.amount, intent.token, &intent.note)?;
+                (intent.signer, intent.feeToken, intent.relayerFee, intent.nonce)
+            }
+            INTENT_ADD_BENEFICIARY => {
+                let intent = <AddBeneficiary as SolType>::abi_decode(&data, true).map_err(malformed)?;
+                self.use_intent(&intent, intent.signer, intent.nonce, intent.deadline, intent.relayerFee, &signature)?;
+                self.add_beneficiary_internal(
+                    intent.signer,
+                    intent.beneficiary,
+                    intent.name,
+                    intent.relationship,
+                    intent.amount,
+                    intent.token,
+                    intent.frequency,
+                )?;
+                (intent.signer, intent.feeToken, intent.relayerFee, intent.nonce)
+            }
+            INTENT_WITHDRAW => {
+                let intent = <Withdraw as SolType>::abi_decode(&data, true).map_err(malformed)?;
+                self.use_intent(&intent, intent.signer, intent.nonce, intent.deadline, intent.relayerFee, &signature)?;
+                self.withdraw_balance_internal(intent.signer, intent.token, intent.amount, intent.signer)?;
+                (intent.signer, intent.feeToken, intent.relayerFee, intent.nonce)
+            }
+            _ => return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {})),
+        };
+        
+        let relayer = self.vm().msg_sender();
+        if relayer_fee > U256::ZERO {
+            let balance = self.users.get(signer).token_balances.get(fee_token);
+            if balance < relayer_fee {
+                return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
+            }
+            self.check_liabilities(fee_token, relayer_fee)?;
+            self.users.setter(signer).token_balances.setter(fee_token).set(balance - relayer_fee);
+            self.decrease_liabilities(fee_token, relayer_fee);
+            self.token_transfer(fee_token, relayer, relayer_fee)?;
+        }
+        
+        log(self.vm(), SignedIntentExecuted {
+            signer,
+            relayer,
+            kind,
+            nonce,
+            feeToken: fee_token,
+            relayerFee: relayer_fee,
+        });
+        
         Ok(())
     }
+    
+    /// The nonce the user's next signed intent must carry.
+    pub fn get_intent_nonce(&self, user: Address) -> U256 {
+        self.intent_nonces.get(user)
+    }
+    
+    /// The EIP-712 domain separator signed intents are hashed under.
+    pub fn domain_separator(&self) -> B256 {
+        self.intent_domain().separator()
+    }
 
     // === ADMIN FUNCTIONS === //
     
     pub fn add_supported_token(&mut self, token: Address) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+        self.only_role(TOKEN_MANAGER_ROLE)?;
         self.supported_tokens.setter(token).set(true);
         Ok(())
     }
     
-    pub fn remove_supported_token(&mut self, token: Address) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
-        self.supported_tokens.setter(token).set(false);
-        Ok(())
+    /// Queues the removal of a supported token; see `execute_governance_change`.
+    pub fn remove_supported_token(&mut self, token: Address) -> Result<U256, RemittanceErrors> {
+        self.only_role(TOKEN_MANAGER_ROLE)?;
+        Ok(self.queue_governance_change(CHANGE_REMOVE_TOKEN, U256::ZERO, token))
     }
     
     pub fn set_daily_limit(&mut self, user: Address, limit: U256) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+        self.only_role(LIMIT_MANAGER_ROLE)?;
         self.daily_limits.setter(user).set(limit);
         Ok(())
     }
     
+    pub fn set_payment_interval_bounds(&mut self, min_interval: U256, max_interval: U256) -> Result<(), RemittanceErrors> {
+        self.only_role(LIMIT_MANAGER_ROLE)?;
+        
+        if min_interval == U256::ZERO || min_interval > max_interval || max_interval > U256::from(MAX_PAYMENT_INTERVAL) {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        self.min_payment_interval.set(min_interval);
+        self.max_payment_interval.set(max_interval);
+        
+        log(self.vm(), PaymentIntervalBoundsUpdated {
+            minInterval: min_interval,
+            maxInterval: max_interval,
+        });
+        Ok(())
+    }
+    
+    pub fn get_payment_interval_bounds(&self) -> (U256, U256) {
+        (self.min_payment_interval.get(), self.max_payment_interval.get())
+    }
+    
     pub fn pause(&mut self) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+        self.only_role(PAUSER_ROLE)?;
         self.paused.set(true);
         Ok(())
     }
     
     pub fn unpause(&mut self) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+        self.only_role(PAUSER_ROLE)?;
         self.paused.set(false);
         Ok(())
     }
+    
+    /// Suspends or reinstates a registered user; suspended users cannot act.
+    pub fn set_user_active(&mut self, user: Address, active: bool) -> Result<(), RemittanceErrors> {
+        self.only_role(COMPLIANCE_ROLE)?;
+        if !self.registered_users.get(user) {
+            return Err(RemittanceErrors::NotRegistered(NotRegistered {}));
+        }
+        self.users.setter(user).is_active.set(active);
+        
+        // Suspended users' schedules leave the due queue and rejoin on reactivation
+        let beneficiary_count = self.beneficiary_counts.get(user).to::<u64>();
+        for index in 0..beneficiary_count {
+            self.sync_due_queue(user, U256::from(index));
+        }
+        
+        log(self.vm(), UserStatusChanged { user, active });
+        Ok(())
+    }
+
+    // === OWNERSHIP === //
+    
+    /// Starts a handover; `new_owner` must call `accept_ownership` to complete it,
+    /// at which point the current owner's roles move with the ownership.
+    pub fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), RemittanceErrors> {
+        self.only_owner()?;
+        self.pending_owner.set(new_owner);
+        log(self.vm(), OwnershipTransferStarted {
+            previousOwner: self.owner.get(),
+            newOwner: new_owner,
+        });
+        Ok(())
+    }
+    
+    pub fn accept_ownership(&mut self) -> Result<(), RemittanceErrors> {
+        let sender = self.vm().msg_sender();
+        if sender == Address::ZERO || sender != self.pending_owner.get() {
+            return Err(RemittanceErrors::Unauthorized(Unauthorized {}));
+        }
+        let previous_owner = self.owner.get();
+        self.owner.set(sender);
+        self.pending_owner.set(Address::ZERO);
+        for role in ALL_ROLES {
+            if self.roles.getter(role).get(previous_owner) {
+                self.revoke_role_internal(role, previous_owner);
+                self.grant_role_internal(role, sender);
+            }
+        }
+        log(self.vm(), OwnershipTransferred {
+            previousOwner: previous_owner,
+            newOwner: sender,
+        });
+        Ok(())
+    }
+    
+    /// Leaves the contract without an owner, so roles can no longer be granted or
+    /// revoked. The owner gives up its own roles too; roles held by other accounts
+    /// stay as they are.
+    pub fn renounce_ownership(&mut self) -> Result<(), RemittanceErrors> {
+        self.only_owner()?;
+        let previous_owner = self.owner.get();
+        self.owner.set(Address::ZERO);
+        self.pending_owner.set(Address::ZERO);
+        for role in ALL_ROLES {
+            if self.roles.getter(role).get(previous_owner) {
+                self.revoke_role_internal(role, previous_owner);
+            }
+        }
+        log(self.vm(), OwnershipTransferred {
+            previousOwner: previous_owner,
+            newOwner: Address::ZERO,
+        });
+        Ok(())
+    }
+    
+    pub fn owner(&self) -> Address {
+        self.owner.get()
+    }
+    
+    pub fn pending_owner(&self) -> Address {
+        self.pending_owner.get()
+    }
+
+    // === ACCESS CONTROL === //
+    
+    pub fn grant_role(&mut self, role: B256, account: Address) -> Result<(), RemittanceErrors> {
+        self.only_owner()?;
+        if !ALL_ROLES.contains(&role) || account == Address::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        self.grant_role_internal(role, account);
+        Ok(())
+    }
+    
+    pub fn revoke_role(&mut self, role: B256, account: Address) -> Result<(), RemittanceErrors> {
+        self.only_owner()?;
+        self.revoke_role_internal(role, account);
+        Ok(())
+    }
+    
+    /// Lets a holder drop one of its own roles, e.g. when a key is retired.
+    pub fn renounce_role(&mut self, role: B256) -> Result<(), RemittanceErrors> {
+        let account = self.vm().msg_sender();
+        self.revoke_role_internal(role, account);
+        Ok(())
+    }
+    
+    pub fn has_role(&self, role: B256, account: Address) -> bool {
+        self.roles.getter(role).get(account)
+    }
 
     // === VIEW FUNCTIONS === //
     
@@ -678,6 +1738,7 @@ impl UniversalRemittance {
         self.users.get(user).token_balances.get(token)
     }
     
+    #[allow(clippy::type_complexity)]
     pub fn get_beneficiary(&self, user: Address, index: U256) -> Result<(Address, String, String, U256, Address, U256, U256, bool, U256), RemittanceErrors> {
         let beneficiary_count = self.beneficiary_counts.get(user);
         if index >= beneficiary_count {
@@ -686,6 +1747,11 @@ impl UniversalRemittance {
         
         let user_beneficiaries = self.user_beneficiaries.get(user);
         let beneficiary = user_beneficiaries.get(index);
+        // Completed schedules stay visible, removed beneficiaries do not
+        if !beneficiary.is_active.get() && !beneficiary.completed.get() {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        let current_time = U256::from(self.vm().block_timestamp());
         Ok((
             beneficiary.beneficiary_address.get(),
             beneficiary.name.get_string(),
@@ -694,7 +1760,7 @@ impl UniversalRemittance {
             beneficiary.token.get(),
             beneficiary.frequency.get(),
             beneficiary.last_payment.get(),
-            beneficiary.is_active.get(),
+            beneficiary.is_active.get() && !schedule_ended(&beneficiary, current_time),
             beneficiary.total_sent.get(),
         ))
     }
@@ -703,6 +1769,50 @@ impl UniversalRemittance {
         self.beneficiary_counts.get(user)
     }
     
+    /// Returns the `(day, month)` a calendar schedule is pinned to; zeros mean
+    /// the date of the first payment will be used.
+    pub fn get_payment_anchor(&self, user: Address, index: U256) -> Result<(u8, u8), RemittanceErrors> {
+        let beneficiary_count = self.beneficiary_counts.get(user);
+        if index >= beneficiary_count {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        
+        let user_beneficiaries = self.user_beneficiaries.get(user);
+        let beneficiary = user_beneficiaries.get(index);
+        Ok((beneficiary.anchor_day.get().to::<u8>(), beneficiary.anchor_month.get().to::<u8>()))
+    }
+    
+    /// Returns `(interval_seconds, start_time, end_time, max_executions, executions)`.
+    pub fn get_beneficiary_schedule(&self, user: Address, index: U256) -> Result<(U256, U256, U256, U256, U256), RemittanceErrors> {
+        let beneficiary_count = self.beneficiary_counts.get(user);
+        if index >= beneficiary_count {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        
+        let user_beneficiaries = self.user_beneficiaries.get(user);
+        let beneficiary = user_beneficiaries.get(index);
+        Ok((
+            beneficiary.interval_seconds.get(),
+            beneficiary.start_time.get(),
+            beneficiary.end_time.get(),
+            beneficiary.max_executions.get(),
+            beneficiary.executions.get(),
+        ))
+    }
+    
+    /// Returns the beneficiary's `(policy, max_periods)` catch-up setting.
+    pub fn get_catch_up_policy(&self, user: Address, index: U256) -> Result<(u8, U256), RemittanceErrors> {
+        let beneficiary_count = self.beneficiary_counts.get(user);
+        if index >= beneficiary_count {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        
+        let user_beneficiaries = self.user_beneficiaries.get(user);
+        let beneficiary = user_beneficiaries.get(index);
+        Ok((beneficiary.catch_up_policy.get().to::<u8>(), beneficiary.catch_up_max.get()))
+    }
+    
+    #[allow(clippy::type_complexity)]
     pub fn get_payment(&self, payment_id: U256) -> Result<(Address, Address, U256, Address, U256, U256, String, bool), RemittanceErrors> {
         if payment_id >= self.payment_count.get() {
             return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
@@ -721,17 +1831,64 @@ impl UniversalRemittance {
         ))
     }
     
-    pub fn is_token_supported(&self, token: Address) -> bool {
-        self.supported_tokens.get(token)
+    /// Returns `(execute_after, cancelled)` for a payment; both are unset for
+    /// payments that were not scheduled.
+    pub fn get_payment_schedule(&self, payment_id: U256) -> Result<(U256, bool), RemittanceErrors> {
+        if payment_id >= self.payment_count.get() {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        let payment = self.payments.get(payment_id);
+        Ok((payment.execute_after.get(), payment.cancelled.get()))
     }
     
-    pub fn get_daily_limit(&self, user: Address) -> U256 {
-        self.daily_limits.get(user)
+    /// Returns the split payment group a payment belongs to, or 0 if none.
+    pub fn get_payment_group(&self, payment_id: U256) -> Result<U256, RemittanceErrors> {
+        if payment_id >= self.payment_count.get() {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        Ok(self.payments.get(payment_id).group_id.get())
     }
     
-    pub fn get_daily_spent(&self, user: Address) -> U256 {
-        let today = U256::from(self.vm().block_timestamp() / 86400);
-        self.daily_spent.getter(user).get(today)
+    /// Returns the id of the payment `sender` made with `reference`.
+    pub fn get_payment_by_reference(&self, sender: Address, reference: B256) -> Result<U256, RemittanceErrors> {
+        let id = self.payment_references.getter(sender).get(reference);
+        if reference == B256::ZERO || id == U256::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        Ok(id - U256::from(1))
+    }
+    
+    /// Returns the sender's reference for a payment, or zero if none.
+    pub fn get_payment_reference(&self, payment_id: U256) -> Result<B256, RemittanceErrors> {
+        if payment_id >= self.payment_count.get() {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        Ok(self.payments.get(payment_id).reference.get())
+    }
+    
+    pub fn get_claimable(&self, recipient: Address, token: Address) -> U256 {
+        self.claimable.getter(recipient).get(token)
+    }
+    
+    /// Internal transfer fees in `token` waiting for `claim_fees`.
+    pub fn get_accrued_fees(&self, token: Address) -> U256 {
+        self.accrued_fees.get(token)
+    }
+    
+    pub fn is_token_supported(&self, token: Address) -> bool {
+        self.supported_tokens.get(token)
+    }
+    
+    pub fn get_daily_limit(&self, user: Address) -> U256 {
+        self.daily_limits.get(user)
+    }
+    
+    pub fn get_daily_spent(&self, user: Address) -> U256 {
+        let today = U256::from(self.vm().block_timestamp() / 86400);
+        self.daily_spent.getter(user).get(today)
     }
     
     pub fn get_contract_stats(&self) -> (U256, U256, U256, bool, Address) {
@@ -753,10 +1910,140 @@ impl UniversalRemittance {
         Ok(())
     }
     
+    fn queue_governance_change(&mut self, kind: u8, value: U256, target: Address) -> U256 {
+        let change_id = self.governance_change_count.get();
+        let eta = U256::from(self.vm().block_timestamp()) + self.governance_delay.get();
+        
+        let mut change = self.governance_changes.setter(change_id);
+        change.kind.set(U8::from(kind));
+        change.value.set(value);
+        change.target.set(target);
+        change.eta.set(eta);
+        
+        self.governance_change_count.set(change_id + U256::from(1));
+        self.pending_change_ids.push(change_id);
+        
+        log(self.vm(), GovernanceChangeQueued {
+            changeId: change_id,
+            kind,
+            value,
+            target,
+            eta,
+        });
+        
+        change_id
+    }
+    
+    fn pending_governance_kind(&self, change_id: U256) -> Result<u8, RemittanceErrors> {
+        if change_id >= self.governance_change_count.get() {
+            return Err(RemittanceErrors::NothingQueued(NothingQueued {}));
+        }
+        let change = self.governance_changes.get(change_id);
+        if change.executed.get() || change.cancelled.get() {
+            return Err(RemittanceErrors::NothingQueued(NothingQueued {}));
+        }
+        Ok(change.kind.get().to::<u8>())
+    }
+    
+    fn only_governance_role(&self, kind: u8) -> Result<(), RemittanceErrors> {
+        match kind {
+            CHANGE_PLATFORM_FEE => self.only_role(FEE_MANAGER_ROLE),
+            CHANGE_TREASURY => self.only_role(TREASURY_ADMIN_ROLE),
+            CHANGE_REMOVE_TOKEN => self.only_role(TOKEN_MANAGER_ROLE),
+            CHANGE_INTERNAL_TRANSFER_FEE => self.only_role(FEE_MANAGER_ROLE),
+            _ => self.only_owner(),
+        }
+    }
+    
+    fn remove_pending_change(&mut self, change_id: U256) {
+        let len = self.pending_change_ids.len();
+        for i in 0..len {
+            if self.pending_change_ids.get(i) == Some(change_id) {
+                let last = self.pending_change_ids.get(len - 1).unwrap_or_default();
+                if let Some(mut slot) = self.pending_change_ids.setter(i) {
+                    slot.set(last);
+                }
+                self.pending_change_ids.pop();
+                return;
+            }
+        }
+    }
+    
+    /// Accepts 0 (manual) or a whole number of days inside the interval bounds.
+    fn validate_frequency(&self, frequency: U256) -> Result<(), RemittanceErrors> {
+        if frequency == U256::ZERO {
+            return Ok(());
+        }
+        let interval = frequency.saturating_mul(U256::from(86400));
+        if interval < self.min_payment_interval.get() || interval > self.max_payment_interval.get() {
+            return Err(RemittanceErrors::InvalidFrequency(InvalidFrequency {}));
+        }
+        Ok(())
+    }
+    
+    fn only_role(&self, role: B256) -> Result<(), RemittanceErrors> {
+        if !self.roles.getter(role).get(self.vm().msg_sender()) {
+            return Err(RemittanceErrors::Unauthorized(Unauthorized {}));
+        }
+        Ok(())
+    }
+    
+    fn grant_role_internal(&mut self, role: B256, account: Address) {
+        if self.roles.getter(role).get(account) {
+            return;
+        }
+        self.roles.setter(role).setter(account).set(true);
+        log(self.vm(), RoleGranted {
+            role,
+            account,
+            sender: self.vm().msg_sender(),
+        });
+    }
+    
+    fn revoke_role_internal(&mut self, role: B256, account: Address) {
+        if !self.roles.getter(role).get(account) {
+            return;
+        }
+        self.roles.setter(role).setter(account).set(false);
+        log(self.vm(), RoleRevoked {
+            role,
+            account,
+            sender: self.vm().msg_sender(),
+        });
+    }
+    
+    /// Emergency withdrawals go to the treasury rather than the owner, which may
+    /// have been renounced; a zero treasury would burn the funds.
+    fn emergency_recipient(&self) -> Result<Address, RemittanceErrors> {
+        let treasury = self.treasury.get();
+        if treasury == Address::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        Ok(treasury)
+    }
+    
     fn only_registered(&self) -> Result<(), RemittanceErrors> {
-        if !self.registered_users.get(self.vm().msg_sender()) {
+        self.only_registered_account(self.vm().msg_sender())
+    }
+    
+    fn only_registered_account(&self, user: Address) -> Result<(), RemittanceErrors> {
+        if !self.registered_users.get(user) {
             return Err(RemittanceErrors::NotRegistered(NotRegistered {}));
         }
+        if !self.users.get(user).is_active.get() {
+            return Err(RemittanceErrors::AccountSuspended(AccountSuspended {}));
+        }
+        Ok(())
+    }
+    
+    fn only_pending_scheduled(&self, payment_id: U256) -> Result<(), RemittanceErrors> {
+        if payment_id >= self.payment_count.get() {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        let payment = self.payments.get(payment_id);
+        if payment.payment_type.get() != U256::from(2) || payment.completed.get() || payment.cancelled.get() {
+            return Err(RemittanceErrors::PaymentNotPending(PaymentNotPending {}));
+        }
         Ok(())
     }
     
@@ -767,6 +2054,28 @@ impl UniversalRemittance {
         Ok(())
     }
     
+    /// Whether payouts from the user's balance are currently restricted to their allowlist.
+    fn withdrawal_allowlist_active(&self, user: Address) -> bool {
+        if !self.withdrawal_allowlist_enabled.get(user) {
+            return false;
+        }
+        let disable_at = self.withdrawal_allowlist_disable_at.get(user);
+        disable_at == U256::ZERO || U256::from(self.vm().block_timestamp()) < disable_at
+    }
+    
+    /// Fails unless `destination` may receive funds from `user`'s balance: it is the
+    /// user, the allowlist is off, or it is listed and past its cooling-off period.
+    fn only_allowed_destination(&self, user: Address, destination: Address) -> Result<(), RemittanceErrors> {
+        if destination == user || !self.withdrawal_allowlist_active(user) {
+            return Ok(());
+        }
+        let usable_from = self.withdrawal_destinations.getter(user).get(destination);
+        if usable_from == U256::ZERO || U256::from(self.vm().block_timestamp()) < usable_from {
+            return Err(RemittanceErrors::DestinationNotAllowed(DestinationNotAllowed {}));
+        }
+        Ok(())
+    }
+    
     fn check_daily_limit(&self, user: Address, amount: U256) -> bool {
         let daily_limit = self.daily_limits.get(user);
         if daily_limit == U256::ZERO {
@@ -784,25 +2093,102 @@ impl UniversalRemittance {
         self.daily_spent.setter(user).setter(today).set(current_spent + amount);
     }
     
+    fn increase_liabilities(&mut self, token: Address, amount: U256) {
+        let current = self.token_liabilities.get(token);
+        self.token_liabilities.setter(token).set(current + amount);
+    }
+    
+    /// Liabilities always cover every internal balance, scheduled reserve, claimable
+    /// amount and accrued fee, so a shortfall here means the books are out of sync.
+    /// Callers run this before touching any state, so the decrease that follows
+    /// cannot fail halfway through.
+    fn check_liabilities(&self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        if self.token_liabilities.get(token) < amount {
+            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
+        }
+        Ok(())
+    }
+    
+    /// Only call after `check_liabilities` has passed for `amount`.
+    fn decrease_liabilities(&mut self, token: Address, amount: U256) {
+        let current = self.token_liabilities.get(token);
+        self.token_liabilities.setter(token).set(current.saturating_sub(amount));
+    }
+
     // === BATCH OPERATIONS === //
     
-    pub fn batch_execute_auto_payments(&mut self, users_and_indices: Vec<(Address, U256)>) -> Result<Vec<bool>, RemittanceErrors> {
+    /// Executes each `(user, beneficiary_index)` item independently and returns a
+    /// `(status, execution_id)` pair per item. Status is `BATCH_STATUS_OK` on
+    /// success, otherwise the failing error's `RemittanceErrors::code`, with a zero id.
+    ///
+    /// Stops before an item once too little gas is left to finish it safely, and
+    /// reports how many leading items were processed so the rest can be resubmitted.
+    pub fn batch_execute_auto_payments(&mut self, users_and_indices: Vec<(Address, U256)>) -> Result<(U256, Vec<(u8, U256)>), RemittanceErrors> {
         self.when_not_paused()?;
         
         let mut results = Vec::new();
+        let mut succeeded = 0u64;
         
         for (user, beneficiary_index) in users_and_indices {
-            match self.execute_auto_payments(user, beneficiary_index) {
-                Ok(()) => results.push(true),
-                Err(_) => results.push(false),
+            if !self.has_gas_for_item() {
+                break;
+            }
+            match self.process_auto_payment(user, beneficiary_index) {
+                Ok(execution_id) => {
+                    succeeded += 1;
+                    results.push((BATCH_STATUS_OK, execution_id));
+                }
+                Err(err) => results.push((err.code(), U256::ZERO)),
             }
         }
         
-        Ok(results)
+        log(self.vm(), BatchExecuted {
+            keeper: self.vm().msg_sender(),
+            succeeded: U256::from(succeeded),
+            failed: U256::from(results.len() as u64 - succeeded),
+        });
+        
+        Ok((U256::from(results.len()), results))
     }
     
     // === UTILITY FUNCTIONS === //
     
+    /// Lists up to `limit` queued auto-payments that are due now, earliest first,
+    /// as `(user, beneficiary_index, due_time)`.
+    pub fn peek_due(&self, limit: U256) -> Vec<(Address, U256, U256)> {
+        let current_time = U256::from(self.vm().block_timestamp());
+        let limit = limit.min(U256::from(MAX_DUE_ITEMS)).to::<usize>();
+        let mut due = Vec::new();
+        
+        // Best-first walk of the heap: a node is only visited after its parent
+        let mut frontier = BinaryHeap::new();
+        if let Some(root_time) = self.due_times.get(0) {
+            frontier.push(Reverse((root_time, 0usize)));
+        }
+        while due.len() < limit {
+            let Some(Reverse((due_time, position))) = frontier.pop() else { break };
+            if due_time > current_time {
+                break;
+            }
+            due.push((
+                self.due_users.get(position).unwrap_or_default(),
+                self.due_indices.get(position).unwrap_or_default(),
+                due_time,
+            ));
+            for child in [2 * position + 1, 2 * position + 2] {
+                if let Some(child_time) = self.due_times.get(child) {
+                    frontier.push(Reverse((child_time, child)));
+                }
+            }
+        }
+        
+        due
+    }
+    
+    pub fn get_due_queue_length(&self) -> U256 {
+        U256::from(self.due_times.len())
+    }
+    
     pub fn get_pending_auto_payments(&self, user: Address) -> Vec<U256> {
         let mut pending = Vec::new();
         let beneficiary_count = self.beneficiary_counts.get(user);
@@ -813,14 +2199,11 @@ impl UniversalRemittance {
             let user_beneficiaries = self.user_beneficiaries.get(user);
             let beneficiary = user_beneficiaries.get(index);
             
-            if !beneficiary.is_active.get() || beneficiary.frequency.get() == U256::ZERO {
+            if !beneficiary.is_active.get() || !is_automatic(&beneficiary) || schedule_ended(&beneficiary, current_time) {
                 continue;
             }
             
-            let last_payment = beneficiary.last_payment.get();
-            let frequency_seconds = beneficiary.frequency.get() * U256::from(86400);
-            
-            if last_payment == U256::ZERO || (current_time - last_payment) >= frequency_seconds {
+            if current_time >= next_due_time(&beneficiary) {
                 // Check if user has sufficient balance
                 let amount = beneficiary.amount.get();
                 let token = beneficiary.token.get();
@@ -843,60 +2226,1227 @@ impl UniversalRemittance {
         
         let user_beneficiaries = self.user_beneficiaries.get(user);
         let beneficiary = user_beneficiaries.get(beneficiary_index);
-        if !beneficiary.is_active.get() || beneficiary.frequency.get() == U256::ZERO {
-            return Ok(U256::ZERO); // Manual payment, no scheduled time
+        let current_time = U256::from(self.vm().block_timestamp());
+        if !beneficiary.is_active.get() || !is_automatic(&beneficiary) || schedule_ended(&beneficiary, current_time) {
+            return Ok(U256::ZERO); // Manual, finished or expired: no scheduled time
         }
         
-        let last_payment = beneficiary.last_payment.get();
-        let frequency_seconds = beneficiary.frequency.get() * U256::from(86400);
-        
-        if last_payment == U256::ZERO {
-            return Ok(U256::from(self.vm().block_timestamp())); // Can be executed now
+        let next_due = next_due_time(&beneficiary);
+        if schedule_ended(&beneficiary, next_due) {
+            return Ok(U256::ZERO); // Next run would fall after the end date
         }
-        
-        Ok(last_payment + frequency_seconds)
+        Ok(next_due.max(current_time)) // Current time if it can be executed now
     }
     
     // === EMERGENCY FUNCTIONS === //
     
+    /// Withdraws tokens the contract holds beyond what it owes users to the treasury.
     pub fn emergency_withdraw(&mut self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+        self.only_role(TREASURY_ADMIN_ROLE)?;
         
-        let token_contract = IERC20::new(token);
-        let owner_addr = self.owner.get();
+        let balance = self.token_balance_of(token)?;
+        let surplus = balance.saturating_sub(self.token_liabilities.get(token));
+        if amount > surplus {
+            return Err(RemittanceErrors::ExceedsSurplus(ExceedsSurplus {}));
+        }
         
-        match token_contract.transfer(&mut *self, owner_addr, amount) {
-            Ok(success) => {
-                if !success {
-                    return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
-                }
-            }
-            Err(_) => return Err(RemittanceErrors::TransferFailed(TransferFailed {})),
+        let treasury_addr = self.emergency_recipient()?;
+        self.token_transfer(token, treasury_addr, amount)?;
+        
+        log(self.vm(), EmergencyWithdrawal {
+            token,
+            to: treasury_addr,
+            amount,
+            shortfall: U256::ZERO,
+        });
+        
+        Ok(())
+    }
+    
+    /// Queues a withdrawal that may cut into user funds; it unlocks after
+    /// `EMERGENCY_WITHDRAW_DELAY` so users have time to exit first.
+    pub fn queue_emergency_withdraw(&mut self, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        self.only_role(TREASURY_ADMIN_ROLE)?;
+        if amount == U256::ZERO {
+            return Err(RemittanceErrors::InvalidAmount(InvalidAmount {}));
+        }
+        
+        let eta = U256::from(self.vm().block_timestamp() + EMERGENCY_WITHDRAW_DELAY);
+        self.emergency_amounts.setter(token).set(amount);
+        self.emergency_etas.setter(token).set(eta);
+        
+        log(self.vm(), EmergencyWithdrawalQueued { token, amount, eta });
+        
+        Ok(())
+    }
+    
+    pub fn cancel_emergency_withdraw(&mut self, token: Address) -> Result<(), RemittanceErrors> {
+        self.only_role(TREASURY_ADMIN_ROLE)?;
+        
+        let amount = self.emergency_amounts.get(token);
+        if amount == U256::ZERO {
+            return Err(RemittanceErrors::NothingQueued(NothingQueued {}));
         }
+        self.emergency_amounts.setter(token).set(U256::ZERO);
+        self.emergency_etas.setter(token).set(U256::ZERO);
+        
+        log(self.vm(), EmergencyWithdrawalCancelled { token, amount });
         
         Ok(())
     }
     
-    pub fn update_platform_fee(&mut self, new_fee_percent: U256) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+    pub fn execute_emergency_withdraw(&mut self, token: Address) -> Result<(), RemittanceErrors> {
+        self.only_role(TREASURY_ADMIN_ROLE)?;
+        
+        let amount = self.emergency_amounts.get(token);
+        if amount == U256::ZERO {
+            return Err(RemittanceErrors::NothingQueued(NothingQueued {}));
+        }
+        if U256::from(self.vm().block_timestamp()) < self.emergency_etas.get(token) {
+            return Err(RemittanceErrors::TimelockNotReady(TimelockNotReady {}));
+        }
+        
+        let balance = self.token_balance_of(token)?;
+        let remaining = balance.saturating_sub(amount);
+        let shortfall = self.token_liabilities.get(token).saturating_sub(remaining);
+        
+        self.emergency_amounts.setter(token).set(U256::ZERO);
+        self.emergency_etas.setter(token).set(U256::ZERO);
+        
+        let treasury_addr = self.emergency_recipient()?;
+        self.token_transfer(token, treasury_addr, amount)?;
+        
+        log(self.vm(), EmergencyWithdrawal {
+            token,
+            to: treasury_addr,
+            amount,
+            shortfall,
+        });
+        
+        Ok(())
+    }
+    
+    /// Returns `(contract_balance, liabilities, surplus, shortfall)` for a token.
+    pub fn get_token_solvency(&self, token: Address) -> Result<(U256, U256, U256, U256), RemittanceErrors> {
+        let balance = self.token_balance_of(token)?;
+        let liabilities = self.token_liabilities.get(token);
+        Ok((
+            balance,
+            liabilities,
+            balance.saturating_sub(liabilities),
+            liabilities.saturating_sub(balance),
+        ))
+    }
+    
+    pub fn get_pending_emergency_withdraw(&self, token: Address) -> (U256, U256) {
+        (self.emergency_amounts.get(token), self.emergency_etas.get(token))
+    }
+    
+    /// Queues a new platform fee; it takes effect via `execute_governance_change`
+    /// once the governance delay has passed.
+    pub fn update_platform_fee(&mut self, new_fee_percent: U256) -> Result<U256, RemittanceErrors> {
+        self.only_role(FEE_MANAGER_ROLE)?;
         
         // Max fee of 1% (100 basis points)
         if new_fee_percent > U256::from(100) {
             return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
         
-        self.platform_fee_percent.set(new_fee_percent);
-        Ok(())
+        Ok(self.queue_governance_change(CHANGE_PLATFORM_FEE, new_fee_percent, Address::ZERO))
     }
     
-    pub fn update_treasury(&mut self, new_treasury: Address) -> Result<(), RemittanceErrors> {
-        self.only_owner()?;
+    /// Queues a new fee for `internal_transfer`, capped like the platform fee; see
+    /// `update_platform_fee`.
+    pub fn update_internal_transfer_fee(&mut self, new_fee_percent: U256) -> Result<U256, RemittanceErrors> {
+        self.only_role(FEE_MANAGER_ROLE)?;
+        
+        if new_fee_percent > U256::from(100) {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        Ok(self.queue_governance_change(CHANGE_INTERNAL_TRANSFER_FEE, new_fee_percent, Address::ZERO))
+    }
+    
+    pub fn get_internal_transfer_fee(&self) -> U256 {
+        self.internal_transfer_fee_percent.get()
+    }
+    
+    /// Queues a new treasury address; see `update_platform_fee`.
+    pub fn update_treasury(&mut self, new_treasury: Address) -> Result<U256, RemittanceErrors> {
+        self.only_role(TREASURY_ADMIN_ROLE)?;
         
         if new_treasury == Address::ZERO {
             return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
         }
         
-        self.treasury.set(new_treasury);
+        Ok(self.queue_governance_change(CHANGE_TREASURY, U256::ZERO, new_treasury))
+    }
+    
+    /// Queues a new governance delay. Shortening it is itself subject to the current delay.
+    pub fn update_governance_delay(&mut self, new_delay: U256) -> Result<U256, RemittanceErrors> {
+        self.only_owner()?;
+        
+        if new_delay > U256::from(MAX_GOVERNANCE_DELAY) {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        Ok(self.queue_governance_change(CHANGE_GOVERNANCE_DELAY, new_delay, Address::ZERO))
+    }
+    
+    pub fn execute_governance_change(&mut self, change_id: U256) -> Result<(), RemittanceErrors> {
+        let kind = self.pending_governance_kind(change_id)?;
+        self.only_governance_role(kind)?;
+        
+        let change = self.governance_changes.get(change_id);
+        if U256::from(self.vm().block_timestamp()) < change.eta.get() {
+            return Err(RemittanceErrors::TimelockNotReady(TimelockNotReady {}));
+        }
+        let value = change.value.get();
+        let target = change.target.get();
+        
+        match kind {
+            CHANGE_PLATFORM_FEE => self.platform_fee_percent.set(value),
+            CHANGE_TREASURY => self.treasury.set(target),
+            CHANGE_REMOVE_TOKEN => self.supported_tokens.setter(target).set(false),
+            CHANGE_INTERNAL_TRANSFER_FEE => self.internal_transfer_fee_percent.set(value),
+            _ => self.governance_delay.set(value),
+        }
+        
+        self.governance_changes.setter(change_id).executed.set(true);
+        self.remove_pending_change(change_id);
+        
+        log(self.vm(), GovernanceChangeExecuted { changeId: change_id });
+        Ok(())
+    }
+    
+    pub fn cancel_governance_change(&mut self, change_id: U256) -> Result<(), RemittanceErrors> {
+        let kind = self.pending_governance_kind(change_id)?;
+        self.only_governance_role(kind)?;
+        
+        self.governance_changes.setter(change_id).cancelled.set(true);
+        self.remove_pending_change(change_id);
+        
+        log(self.vm(), GovernanceChangeCancelled { changeId: change_id });
+        Ok(())
+    }
+    
+    /// Lists queued changes as `(id, kind, value, target, eta)`, where kind is
+    /// 0=platform fee, 1=treasury, 2=remove token, 3=governance delay,
+    /// 4=internal transfer fee.
+    pub fn get_pending_governance_changes(&self) -> Vec<(U256, U256, U256, Address, U256)> {
+        let mut pending = Vec::new();
+        for i in 0..self.pending_change_ids.len() {
+            let change_id = self.pending_change_ids.get(i).unwrap_or_default();
+            let change = self.governance_changes.get(change_id);
+            pending.push((
+                change_id,
+                U256::from(change.kind.get()),
+                change.value.get(),
+                change.target.get(),
+                change.eta.get(),
+            ));
+        }
+        pending
+    }
+    
+    pub fn get_governance_delay(&self) -> U256 {
+        self.governance_delay.get()
+    }
+}
+
+// Internal helpers kept out of the contract ABI
+impl UniversalRemittance {
+    /// `register_user` for `sender`, which is the caller or an intent's signer;
+    /// likewise for the other `_internal` entry points below.
+    fn register_user_internal(
+        &mut self,
+        sender: Address,
+        name: String,
+        country: String,
+        phone_number: String,
+    ) -> Result<(), RemittanceErrors> {
+        if self.registered_users.get(sender) {
+            return Err(RemittanceErrors::UserAlreadyRegistered(UserAlreadyRegistered {}));
+        }
+        
+        let block_timestamp = U256::from(self.vm().block_timestamp());
+        
+        let mut profile = self.users.setter(sender);
+        profile.name.set_str(&name);
+        profile.country.set_str(&country);
+        profile.phone_number.set_str(&phone_number);
+        profile.is_active.set(true);
+        profile.total_sent.set(U256::ZERO);
+        profile.total_received.set(U256::ZERO);
+        profile.registration_time.set(block_timestamp);
+        
+        self.registered_users.setter(sender).set(true);
+        
+        log(self.vm(), UserRegistered {
+            user: sender,
+            name,
+            country,
+        });
+        
+        Ok(())
+    }
+
+    /// Pulls `amount` from `funder` and credits it to `user`'s internal balance.
+    fn deposit_internal(&mut self, funder: Address, user: Address, token: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        if amount == U256::ZERO {
+            return Err(RemittanceErrors::InvalidAmount(InvalidAmount {}));
+        }
+        if !self.supported_tokens.get(token) {
+            return Err(RemittanceErrors::NotSupportedToken(NotSupportedToken {}));
+        }
+        
+        let contract_addr = self.vm().contract_address();
+
+        // Transfer tokens to contract
+        self.token_transfer_from(token, funder, contract_addr, amount)?;
+        
+        // Update internal balance
+        let mut user_profile = self.users.setter(user);
+        let current_balance = user_profile.token_balances.get(token);
+        user_profile.token_balances.setter(token).set(current_balance + amount);
+        self.increase_liabilities(token, amount);
+        
+        // Keep a record of who funded someone else's balance
+        if funder != user {
+            let payment_id = self.payment_count.get();
+            let block_timestamp = U256::from(self.vm().block_timestamp());
+            
+            let mut payment = self.payments.setter(payment_id);
+            payment.sender.set(funder);
+            payment.recipient.set(user);
+            payment.amount.set(amount);
+            payment.token.set(token);
+            payment.timestamp.set(block_timestamp);
+            payment.payment_type.set(U256::from(4)); // Deposit for another user
+            payment.completed.set(true);
+            
+            self.payment_count.set(payment_id + U256::from(1));
+        }
+        
+        log(self.vm(), BalanceDeposited {
+            user,
+            funder,
+            token,
+            amount,
+        });
+        
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn send_payment_internal(
+        &mut self,
+        sender: Address,
+        recipient: Address,
+        amount: U256,
+        token: Address,
+        note: &str,
+        reference: B256,
+    ) -> Result<(), RemittanceErrors> {
+        self.only_registered_account(sender)?;
+        
+        if !self.supported_tokens.get(token) || amount == U256::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        if reference != B256::ZERO && self.payment_references.getter(sender).get(reference) != U256::ZERO {
+            return Err(RemittanceErrors::DuplicateReference(DuplicateReference {}));
+        }
+        
+        // Check daily limit if set
+        if !self.check_daily_limit(sender, amount) {
+            return Err(RemittanceErrors::ExceedsLimit(ExceedsLimit {}));
+        }
+        
+        let contract_addr = self.vm().contract_address();
+        
+        // Transfer tokens to contract
+        self.token_transfer_from(token, sender, contract_addr, amount)?;
+        
+        self.settle_manual_payment(sender, recipient, amount, token, note, reference)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_beneficiary_internal(
+        &mut self,
+        sender: Address,
+        beneficiary_address: Address,
+        name: String,
+        relationship: String,
+        amount: U256,
+        token: Address,
+        frequency: U256,
+    ) -> Result<(), RemittanceErrors> {
+        self.only_registered_account(sender)?;
+        
+        if !self.supported_tokens.get(token) || amount == U256::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        self.validate_frequency(frequency)?;
+        self.only_allowed_destination(sender, beneficiary_address)?;
+        
+        let beneficiary_count = self.beneficiary_counts.get(sender);
+        
+        let mut user_beneficiaries_setter = self.user_beneficiaries.setter(sender);
+        let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_count);
+        beneficiary.beneficiary_address.set(beneficiary_address);
+        beneficiary.name.set_str(&name);
+        beneficiary.relationship.set_str(&relationship);
+        beneficiary.amount.set(amount);
+        beneficiary.token.set(token);
+        beneficiary.frequency.set(frequency);
+        beneficiary.last_payment.set(U256::ZERO);
+        beneficiary.is_active.set(true);
+        beneficiary.total_sent.set(U256::ZERO);
+        
+        self.beneficiary_counts.setter(sender).set(beneficiary_count + U256::from(1));
+        
+        self.sync_due_queue(sender, beneficiary_count);
+        
+        log(self.vm(), BeneficiaryAdded {
+            user: sender,
+            beneficiary: beneficiary_address,
+            name,
+            amount,
+            token,
+            frequency,
+        });
+        
+        Ok(())
+    }
+
+    fn send_payment_from_balance_internal(
+        &mut self,
+        sender: Address,
+        recipient: Address,
+        amount: U256,
+        token: Address,
+        note: &str,
+    ) -> Result<(), RemittanceErrors> {
+        self.only_registered_account(sender)?;
+        
+        if !self.supported_tokens.get(token) || amount == U256::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        self.only_allowed_destination(sender, recipient)?;
+        
+        // Check daily limit if set
+        if !self.check_daily_limit(sender, amount) {
+            return Err(RemittanceErrors::ExceedsLimit(ExceedsLimit {}));
+        }
+        
+        // Debit internal balance
+        let current_balance = self.users.get(sender).token_balances.get(token);
+        if current_balance < amount {
+            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
+        }
+        self.check_liabilities(token, amount)?;
+        self.users.setter(sender).token_balances.setter(token).set(current_balance - amount);
+        self.decrease_liabilities(token, amount);
+        
+        self.settle_manual_payment(sender, recipient, amount, token, note, B256::ZERO)
+    }
+
+    fn withdraw_balance_internal(
+        &mut self,
+        sender: Address,
+        token: Address,
+        amount: U256,
+        destination: Address,
+    ) -> Result<(), RemittanceErrors> {
+        self.only_registered_account(sender)?;
+        
+        if !self.supported_tokens.get(token) || amount == U256::ZERO {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+        
+        let current_balance = self.users.get(sender).token_balances.get(token);
+        
+        if current_balance < amount {
+            return Err(RemittanceErrors::InsufficientBalance(InsufficientBalance {}));
+        }
+        self.check_liabilities(token, amount)?;
+        
+        // Update internal balance
+        self.users.setter(sender).token_balances.setter(token).set(current_balance - amount);
+        self.decrease_liabilities(token, amount);
+        
+        // Transfer tokens to the user or their chosen destination
+        self.token_transfer(token, destination, amount)?;
+        
+        log(self.vm(), BalanceWithdrawn {
+            user: sender,
+            token,
+            amount,
+            destination,
+        });
+        
+        Ok(())
+    }
+
+    /// Pays out a manual payment whose funds the contract already holds: takes the
+    /// platform fee, records the payment and updates stats and the daily limit.
+    fn settle_manual_payment(
+        &mut self,
+        sender: Address,
+        recipient: Address,
+        amount: U256,
+        token: Address,
+        note: &str,
+        reference: B256,
+    ) -> Result<(), RemittanceErrors> {
+        // Calculate fee
+        let platform_fee = (amount * self.platform_fee_percent.get()) / U256::from(10000);
+        let net_amount = amount.checked_sub(platform_fee)
+            .ok_or(RemittanceErrors::InvalidAmount(InvalidAmount {}))?;
+        
+        // Send to recipient
+        self.token_transfer(token, recipient, net_amount)?;
+        
+        // Send fee to treasury
+        if platform_fee > U256::ZERO {
+            let treasury_addr = self.treasury.get();
+            self.token_transfer(token, treasury_addr, platform_fee)?;
+        }
+        
+        self.record_manual_payment(sender, recipient, amount, net_amount, token, note, U256::ZERO, reference);
+        
+        // Update user stats
+        let mut sender_profile = self.users.setter(sender);
+        let sender_total = sender_profile.total_sent.get();
+        sender_profile.total_sent.set(sender_total + amount);
+        
+        // Update daily spent
+        self.update_daily_spent(sender, amount);
+        
+        Ok(())
+    }
+
+    /// Stores a completed manual payment, credits the recipient's stats and emits
+    /// `PaymentSent`. `group_id` links the legs of a split payment (0 = none), and a
+    /// non-zero `reference` is indexed for `get_payment_by_reference`.
+    #[allow(clippy::too_many_arguments)]
+    fn record_manual_payment(
+        &mut self,
+        sender: Address,
+        recipient: Address,
+        amount: U256,
+        net_amount: U256,
+        token: Address,
+        note: &str,
+        group_id: U256,
+        reference: B256,
+    ) {
+        let payment_id = self.payment_count.get();
+        let block_timestamp = U256::from(self.vm().block_timestamp());
+        
+        let mut payment = self.payments.setter(payment_id);
+        payment.sender.set(sender);
+        payment.recipient.set(recipient);
+        payment.amount.set(amount);
+        payment.token.set(token);
+        payment.timestamp.set(block_timestamp);
+        payment.payment_type.set(U256::ZERO); // Manual payment
+        payment.note.set_str(note);
+        payment.completed.set(true);
+        payment.group_id.set(group_id);
+        payment.reference.set(reference);
+        
+        self.payment_count.set(payment_id + U256::from(1));
+        if reference != B256::ZERO {
+            self.payment_references.setter(sender).setter(reference).set(payment_id + U256::from(1));
+        }
+        
+        if self.registered_users.get(recipient) {
+            let mut recipient_profile = self.users.setter(recipient);
+            let recipient_total = recipient_profile.total_received.get();
+            recipient_profile.total_received.set(recipient_total + net_amount);
+        }
+        
+        log(self.vm(), PaymentSent {
+            sender,
+            recipient,
+            amount,
+            token,
+            paymentType: U256::ZERO,
+            reference,
+        });
+    }
+
+    /// Pushes `amount` to `recipient`, or credits it to their claimable balance if
+    /// the token refuses the transfer (e.g. a blacklisted address).
+    fn pay_or_park(&mut self, sender: Address, token: Address, recipient: Address, amount: U256) {
+        if self.token_transfer(token, recipient, amount).is_ok() {
+            return;
+        }
+        
+        let mut recipient_claims = self.claimable.setter(recipient);
+        let current = recipient_claims.get(token);
+        recipient_claims.setter(token).set(current + amount);
+        self.increase_liabilities(token, amount);
+        
+        log(self.vm(), PaymentParked {
+            recipient,
+            token,
+            sender,
+            amount,
+        });
+    }
+
+    /// Sends `amount` of `token` out of the contract. Native ETH goes with a plain
+    /// value transfer; callers update storage first, and the entrypoint rejects
+    /// reentrant calls, so the recipient cannot re-enter mid-update.
+    fn token_transfer(&mut self, token: Address, to: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        if token == NATIVE_TOKEN {
+            return self
+                .vm()
+                .transfer_eth(to, amount)
+                .map_err(|_| RemittanceErrors::TransferFailed(TransferFailed {}));
+        }
+        let calldata = IERC20::transferCall { to, amount }.abi_encode();
+        self.call_token(token, &calldata)
+    }
+
+    /// Pulls `amount` of `token` from `from`. Native ETH cannot be pulled, so it
+    /// must instead arrive as exactly the `msg_value` of the caller's own call;
+    /// conversely, ETH sent alongside an ERC20 pull is refused rather than stranded.
+    fn token_transfer_from(&mut self, token: Address, from: Address, to: Address, amount: U256) -> Result<(), RemittanceErrors> {
+        let msg_value = self.vm().msg_value();
+        if token == NATIVE_TOKEN {
+            if from != self.vm().msg_sender() || to != self.vm().contract_address() || msg_value != amount {
+                return Err(RemittanceErrors::InvalidAmount(InvalidAmount {}));
+            }
+            return Ok(());
+        }
+        if msg_value != U256::ZERO {
+            return Err(RemittanceErrors::InvalidAmount(InvalidAmount {}));
+        }
+        let calldata = IERC20::transferFromCall { from, to, amount }.abi_encode();
+        self.call_token(token, &calldata)
+    }
+
+    fn token_balance_of(&self, token: Address) -> Result<U256, RemittanceErrors> {
+        let account = self.vm().contract_address();
+        if token == NATIVE_TOKEN {
+            return Ok(self.vm().balance(account));
+        }
+        let calldata = IERC20::balanceOfCall { account }.abi_encode();
+        let returned = self
+            .vm()
+            .static_call(&Call::new(), token, &calldata)
+            .map_err(|_| RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}))?;
+        <sol_data::Uint<256> as SolType>::abi_decode(&returned, true)
+            .map_err(|_| RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}))
+    }
+
+    fn intent_domain(&self) -> Eip712Domain {
+        Eip712Domain::new(
+            Some(EIP712_NAME.into()),
+            Some(EIP712_VERSION.into()),
+            Some(U256::from(self.vm().chain_id())),
+            Some(self.vm().contract_address()),
+            None,
+        )
+    }
+
+    /// Checks that `intent` is live, carries the signer's next nonce and is signed
+    /// by them, and that the signer's allowlist lets any relayer fee go to the
+    /// caller, then consumes the nonce.
+    fn use_intent<T: SolStruct>(
+        &mut self,
+        intent: &T,
+        signer: Address,
+        nonce: U256,
+        deadline: U256,
+        relayer_fee: U256,
+        signature: &[u8],
+    ) -> Result<(), RemittanceErrors> {
+        if U256::from(self.vm().block_timestamp()) > deadline {
+            return Err(RemittanceErrors::IntentExpired(IntentExpired {}));
+        }
+        if nonce != self.intent_nonces.get(signer) {
+            return Err(RemittanceErrors::InvalidNonce(InvalidNonce {}));
+        }
+        if relayer_fee > U256::ZERO {
+            self.only_allowed_destination(signer, self.vm().msg_sender())?;
+        }
+        
+        let hash = intent.eip712_signing_hash(&self.intent_domain());
+        if signer == Address::ZERO || !self.is_valid_signature(signer, hash, signature) {
+            return Err(RemittanceErrors::InvalidSignature(InvalidSignature {}));
+        }
+        
+        self.intent_nonces.setter(signer).set(nonce + U256::from(1));
+        Ok(())
+    }
+
+    /// Whether `signer` authorised `hash`: an ECDSA signature recovering to it, or,
+    /// for smart-contract wallets such as Safes and 4337 accounts, one the wallet
+    /// accepts through ERC-1271 `isValidSignature`.
+    fn is_valid_signature(&self, signer: Address, hash: B256, signature: &[u8]) -> bool {
+        if self.recover_signer(hash, signature) == Some(signer) {
+            return true;
+        }
+        if self.vm().code_size(signer) == 0 {
+            return false;
+        }
+        
+        let calldata = IERC1271::isValidSignatureCall {
+            hash,
+            signature: signature.to_vec().into(),
+        }
+        .abi_encode();
+        match self.vm().static_call(&Call::new(), signer, &calldata) {
+            Ok(returned) => returned.len() == 32 && returned[..4] == ERC1271_MAGIC_VALUE,
+            Err(_) => false,
+        }
+    }
+
+    /// Recovers the address behind a 65-byte `r || s || v` signature of `hash`
+    /// through the `ecrecover` precompile.
+    fn recover_signer(&self, hash: B256, signature: &[u8]) -> Option<Address> {
+        if signature.len() != 65 {
+            return None;
+        }
+        let (r, s) = (&signature[..32], &signature[32..64]);
+        let v = match signature[64] {
+            v @ (27 | 28) => v,
+            v @ (0 | 1) => v + 27,
+            _ => return None,
+        };
+        if U256::from_be_slice(s) > SECP256K1_HALF_ORDER {
+            return None;
+        }
+        
+        let mut input = Vec::with_capacity(128);
+        input.extend_from_slice(hash.as_slice());
+        input.extend_from_slice(&[0u8; 31]);
+        input.push(v);
+        input.extend_from_slice(r);
+        input.extend_from_slice(s);
+        
+        let returned = self.vm().static_call(&Call::new(), ECRECOVER, &input).ok()?;
+        if returned.len() != 32 {
+            return None;
+        }
+        let recovered = Address::from_slice(&returned[12..]);
+        (recovered != Address::ZERO).then_some(recovered)
+    }
+
+    /// Submits the caller's EIP-2612 permit for this contract to spend `value`.
+    ///
+    /// A failing permit is ignored rather than reverting: anyone who sees it in the
+    /// mempool can submit it first, and the allowance it grants is what matters.
+    /// Without that allowance the following `transferFrom` fails anyway.
+    fn token_permit(&mut self, token: Address, value: U256, deadline: U256, v: u8, r: B256, s: B256) {
+        let owner = self.vm().msg_sender();
+        let spender = self.vm().contract_address();
+        let calldata = IERC20::permitCall { owner, spender, value, deadline, v, r, s }.abi_encode();
+        let _ = self.vm().call(&Call::new(), token, &calldata);
+    }
+
+    /// Makes a raw `transfer`/`transferFrom` call and checks it the way SafeERC20
+    /// does: tokens that return nothing (e.g. legacy USDT) succeed, an explicit
+    /// `false` fails, and an empty return from an address without code fails
+    /// rather than passing for a transfer that never happened.
+    fn call_token(&mut self, token: Address, calldata: &[u8]) -> Result<(), RemittanceErrors> {
+        let returned = self
+            .vm()
+            .call(&Call::new(), token, calldata)
+            .map_err(|_| RemittanceErrors::TransferFailed(TransferFailed {}))?;
+        if returned.is_empty() {
+            if self.vm().code_size(token) == 0 {
+                return Err(RemittanceErrors::TransferFailed(TransferFailed {}));
+            }
+            return Ok(());
+        }
+        match <sol_data::Bool as SolType>::abi_decode(&returned, true) {
+            Ok(true) => Ok(()),
+            _ => Err(RemittanceErrors::TransferFailed(TransferFailed {})),
+        }
+    }
+
+    /// Whether enough gas remains to execute one more batch item and still return.
+    fn has_gas_for_item(&self) -> bool {
+        self.vm().evm_gas_left() >= BATCH_ITEM_GAS_RESERVE
+    }
+
+    /// Mirrors a beneficiary's schedule in the due queue: queued at its next due
+    /// time while it can still run, removed once it is manual, inactive or over,
+    /// or while its owner is suspended.
+    fn sync_due_queue(&mut self, user: Address, beneficiary_index: U256) {
+        let current_time = U256::from(self.vm().block_timestamp());
+        let user_active = self.users.get(user).is_active.get();
+        let due = {
+            let user_beneficiaries = self.user_beneficiaries.get(user);
+            let beneficiary = user_beneficiaries.get(beneficiary_index);
+            let next_due = next_due_time(&beneficiary);
+            let runnable = user_active
+                && beneficiary.is_active.get()
+                && is_automatic(&beneficiary)
+                && !schedule_ended(&beneficiary, next_due.max(current_time));
+            runnable.then_some(next_due)
+        };
+        
+        let position = self.due_positions.getter(user).get(beneficiary_index);
+        match (due, position.checked_sub(U256::from(1))) {
+            (Some(due), None) => self.due_insert(user, beneficiary_index, due),
+            (Some(due), Some(position)) => self.due_rekey(position.to::<usize>(), due),
+            (None, Some(position)) => self.due_remove(position.to::<usize>()),
+            (None, None) => {}
+        }
+    }
+
+    fn due_insert(&mut self, user: Address, beneficiary_index: U256, due: U256) {
+        let position = self.due_times.len();
+        self.due_times.push(due);
+        self.due_users.push(user);
+        self.due_indices.push(beneficiary_index);
+        self.due_positions.setter(user).setter(beneficiary_index).set(U256::from(position + 1));
+        self.due_sift_up(position);
+    }
+
+    fn due_rekey(&mut self, position: usize, due: U256) {
+        let previous = self.due_times.get(position).unwrap_or_default();
+        if let Some(mut slot) = self.due_times.setter(position) {
+            slot.set(due);
+        }
+        if due < previous {
+            self.due_sift_up(position);
+        } else {
+            self.due_sift_down(position);
+        }
+    }
+
+    fn due_remove(&mut self, position: usize) {
+        let last = self.due_times.len() - 1;
+        self.due_swap(position, last);
+        
+        let user = self.due_users.get(last).unwrap_or_default();
+        let beneficiary_index = self.due_indices.get(last).unwrap_or_default();
+        self.due_positions.setter(user).setter(beneficiary_index).set(U256::ZERO);
+        self.due_times.pop();
+        self.due_users.pop();
+        self.due_indices.pop();
+        
+        if position < last {
+            self.due_sift_down(position);
+            self.due_sift_up(position);
+        }
+    }
+
+    fn due_sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if self.due_times.get(parent) <= self.due_times.get(position) {
+                break;
+            }
+            self.due_swap(parent, position);
+            position = parent;
+        }
+    }
+
+    fn due_sift_down(&mut self, mut position: usize) {
+        let len = self.due_times.len();
+        loop {
+            let mut smallest = position;
+            for child in [2 * position + 1, 2 * position + 2] {
+                if child < len && self.due_times.get(child) < self.due_times.get(smallest) {
+                    smallest = child;
+                }
+            }
+            if smallest == position {
+                break;
+            }
+            self.due_swap(position, smallest);
+            position = smallest;
+        }
+    }
+
+    fn due_swap(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        let entry_a = self.due_entry(a);
+        let entry_b = self.due_entry(b);
+        self.set_due_entry(a, entry_b);
+        self.set_due_entry(b, entry_a);
+    }
+
+    fn due_entry(&self, position: usize) -> (U256, Address, U256) {
+        (
+            self.due_times.get(position).unwrap_or_default(),
+            self.due_users.get(position).unwrap_or_default(),
+            self.due_indices.get(position).unwrap_or_default(),
+        )
+    }
+
+    fn set_due_entry(&mut self, position: usize, (due, user, beneficiary_index): (U256, Address, U256)) {
+        if let Some(mut slot) = self.due_times.setter(position) {
+            slot.set(due);
+        }
+        if let Some(mut slot) = self.due_users.setter(position) {
+            slot.set(user);
+        }
+        if let Some(mut slot) = self.due_indices.setter(position) {
+            slot.set(beneficiary_index);
+        }
+        self.due_positions.setter(user).setter(beneficiary_index).set(U256::from(position + 1));
+    }
+
+    /// Executes one scheduled payment and returns its execution id.
+    ///
+    /// Any failure leaves storage exactly as it was, so batch callers can skip
+    /// the item and carry on. The fee leg runs first because it is the only
+    /// transfer that can fail; a refused payout or keeper tip is parked for its
+    /// recipient to claim.
+    fn process_auto_payment(&mut self, user: Address, beneficiary_index: U256) -> Result<U256, RemittanceErrors> {
+        // Get block timestamp before any mutable borrow
+        let current_time = U256::from(self.vm().block_timestamp());
+
+        let beneficiary_count = self.beneficiary_counts.get(user);
+        if beneficiary_index >= beneficiary_count {
+            return Err(RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}));
+        }
+        if !self.users.get(user).is_active.get() {
+            return Err(RemittanceErrors::AccountSuspended(AccountSuspended {}));
+        }
+
+        let user_beneficiaries = self.user_beneficiaries.get(user);
+        let beneficiary = user_beneficiaries.get(beneficiary_index);
+        if !beneficiary.is_active.get() || !is_automatic(&beneficiary) {
+            return Err(RemittanceErrors::InvalidConfiguration(InvalidConfiguration {}));
+        }
+
+        if schedule_ended(&beneficiary, current_time) {
+            return Err(RemittanceErrors::ScheduleEnded(ScheduleEnded {}));
+        }
+        if current_time < next_due_time(&beneficiary) {
+            return Err(RemittanceErrors::FrequencyNotMet(FrequencyNotMet {}));
+        }
+
+        let token = beneficiary.token.get();
+        let beneficiary_address = beneficiary.beneficiary_address.get();
+
+        // Work out how many periods this execution settles against the user's internal balance
+        let user_balance = self.users.get(user).token_balances.get(token);
+        let (periods, paid_through) = periods_to_pay(&beneficiary, current_time, user_balance)?;
+        let amount = beneficiary.amount.get() * periods;
+
+        // Calculate fee
+        let platform_fee = (amount * self.platform_fee_percent.get()) / U256::from(10000);
+        let net_amount = amount.checked_sub(platform_fee)
+            .ok_or(RemittanceErrors::InvalidAmount(InvalidAmount {}))?;
+
+        // Tip third-party keepers from whatever balance is left, up to the user's maximum;
+        // with the allowlist on, only allowlisted keepers are tipped
+        let keeper = self.vm().msg_sender();
+        let mut keeper_tip = U256::ZERO;
+        if keeper != user && self.only_allowed_destination(user, keeper).is_ok() {
+            keeper_tip = self.keeper_tips.getter(user).get(token).min(user_balance - amount);
+        }
+        self.check_liabilities(token, amount + keeper_tip)?;
+
+        // Update user's internal balance
+        self.users.setter(user).token_balances.setter(token).set(user_balance - amount);
+        self.decrease_liabilities(token, amount);
+
+        // Send fee to treasury, restoring the debit if the token refuses it
+        if platform_fee > U256::ZERO {
+            let treasury_addr = self.treasury.get();
+            if let Err(err) = self.token_transfer(token, treasury_addr, platform_fee) {
+                self.users.setter(user).token_balances.setter(token).set(user_balance);
+                self.increase_liabilities(token, amount);
+                return Err(err);
+            }
+        }
+
+        // Transfer to beneficiary
+        self.pay_or_park(user, token, beneficiary_address, net_amount);
+
+        // Pay the keeper tip worked out above
+        if keeper_tip > U256::ZERO {
+            self.users.setter(user).token_balances.setter(token).set(user_balance - amount - keeper_tip);
+            self.decrease_liabilities(token, keeper_tip);
+            self.pay_or_park(user, token, keeper, keeper_tip);
+        }
+
+        // Re-borrow to update beneficiary
+        let mut completed_after = None;
+        {
+            let mut user_beneficiaries_setter = self.user_beneficiaries.setter(user);
+            let mut beneficiary = user_beneficiaries_setter.setter(beneficiary_index);
+            beneficiary.last_payment.set(paid_through);
+            // Calendar schedules without an explicit anchor keep the date of their first payment
+            if is_calendar(&beneficiary) && beneficiary.anchor_day.get() == U8::ZERO {
+                let (_, month, day) = civil_from_timestamp(paid_through.saturating_to::<u64>());
+                beneficiary.anchor_day.set(U8::from(day));
+                if beneficiary.frequency.get() == U256::from(FREQUENCY_YEARLY) {
+                    beneficiary.anchor_month.set(U8::from(month));
+                }
+            }
+            let beneficiary_total = beneficiary.total_sent.get();
+            beneficiary.total_sent.set(beneficiary_total + amount);
+
+            let executions = beneficiary.executions.get() + periods;
+            beneficiary.executions.set(executions);
+            let max_executions = beneficiary.max_executions.get();
+            if max_executions != U256::ZERO && executions >= max_executions {
+                beneficiary.is_active.set(false);
+                beneficiary.completed.set(true);
+                completed_after = Some(executions);
+            }
+        }
+
+        self.sync_due_queue(user, beneficiary_index);
+
+        // Update user stats
+        {
+            let mut user_profile_setter = self.users.setter(user);
+            let user_total = user_profile_setter.total_sent.get();
+            user_profile_setter.total_sent.set(user_total + amount);
+        }
+
+        // Update recipient stats if registered
+        if self.registered_users.get(beneficiary_address) {
+            let mut recipient_profile = self.users.setter(beneficiary_address);
+            let recipient_total = recipient_profile.total_received.get();
+            recipient_profile.total_received.set(recipient_total + net_amount);
+        }
+
+        // Record execution
+        let execution_id = self.execution_count.get();
+        self.execution_count.set(execution_id + U256::from(1));
+
+        log(self.vm(), AutoPaymentExecuted {
+            sender: user,
+            beneficiary: beneficiary_address,
+            amount,
+            token,
+            executionId: execution_id,
+            periods,
+            keeper,
+            keeperTip: keeper_tip,
+        });
+        if let Some(executions) = completed_after {
+            log(self.vm(), PaymentScheduleCompleted {
+                user,
+                beneficiaryIndex: beneficiary_index,
+                executions,
+            });
+        }
+
+        Ok(execution_id)
+    }
+}
+
+// Beneficiary schedule helpers, with Gregorian calendar arithmetic (UTC) for
+// monthly and yearly schedules
+
+/// Earliest timestamp at which the beneficiary's next auto-payment may run.
+///
+/// The first payment is due at the start time, or for a pinned monthly or yearly
+/// schedule on the first anchor date at or after it. After that, custom intervals
+/// and day-based frequencies count from the last payment, while monthly and yearly
+/// schedules fall due at midnight on the anchor date following the last payment,
+/// clamped to the end of short months, so late executions never drift.
+fn next_due_time(beneficiary: &Beneficiary) -> U256 {
+    let last_payment = beneficiary.last_payment.get();
+    if last_payment != U256::ZERO {
+        return due_after(beneficiary, last_payment);
+    }
+    let start_time = beneficiary.start_time.get();
+    if !is_calendar(beneficiary) || beneficiary.anchor_day.get() == U8::ZERO {
+        return start_time;
+    }
+    let due = due_after(beneficiary, start_time.saturating_sub(U256::from(86400)));
+    if due >= start_time {
+        due
+    } else {
+        due_after(beneficiary, start_time)
+    }
+}
+
+/// Length of the beneficiary's period in seconds, or `None` when it follows the calendar.
+fn fixed_period(beneficiary: &Beneficiary) -> Option<U256> {
+    let interval = beneficiary.interval_seconds.get();
+    if interval != U256::ZERO {
+        return Some(interval);
+    }
+    if is_calendar(beneficiary) {
+        return None;
+    }
+    Some(beneficiary.frequency.get().saturating_mul(U256::from(86400)))
+}
+
+/// The first due time strictly after the period boundary `from`.
+fn due_after(beneficiary: &Beneficiary, from: U256) -> U256 {
+    if let Some(period) = fixed_period(beneficiary) {
+        return from.saturating_add(period);
+    }
+
+    // Dates beyond the u64 range are never reached
+    let Ok(from) = u64::try_from(from) else {
+        return U256::MAX;
+    };
+    let yearly = beneficiary.frequency.get() == U256::from(FREQUENCY_YEARLY);
+    let (year, month, day) = civil_from_timestamp(from);
+    let anchor_day = match beneficiary.anchor_day.get().to::<u32>() {
+        0 => day,
+        anchor => anchor,
+    };
+
+    let (mut due_year, mut due_month) = (year, month);
+    if yearly {
+        due_month = match beneficiary.anchor_month.get().to::<u32>() {
+            0 => month,
+            anchor => anchor,
+        };
+    }
+    if (due_month, anchor_day.min(days_in_month(due_year, due_month))) <= (month, day) {
+        if yearly {
+            due_year += 1;
+        } else if due_month == 12 {
+            due_year += 1;
+            due_month = 1;
+        } else {
+            due_month += 1;
+        }
+    }
+
+    let due_day = anchor_day.min(days_in_month(due_year, due_month));
+    U256::from(days_from_civil(due_year, due_month, due_day)) * U256::from(86400)
+}
+
+/// Works out how many periods one execution pays under the beneficiary's catch-up
+/// policy, and the due time of the last of them, which becomes `last_payment`.
+///
+/// Missed periods the policy gives up on are the oldest ones; periods the balance
+/// or the execution cap cannot cover yet stay due for a later execution.
+fn periods_to_pay(beneficiary: &Beneficiary, now: U256, balance: U256) -> Result<(U256, U256), RemittanceErrors> {
+    let amount = beneficiary.amount.get();
+    let insufficient = || RemittanceErrors::InsufficientBalance(InsufficientBalance {});
+
+    // A schedule with no start date takes its phase from its first payment
+    if beneficiary.last_payment.get() == U256::ZERO && beneficiary.start_time.get() == U256::ZERO {
+        if balance < amount {
+            return Err(insufficient());
+        }
+        return Ok((U256::from(1), now));
+    }
+
+    let first_due = next_due_time(beneficiary);
+    let horizon = match beneficiary.end_time.get() {
+        end_time if end_time != U256::ZERO => now.min(end_time),
+        _ => now,
+    };
+    let due = count_due_periods(beneficiary, first_due, horizon);
+    let owed = match beneficiary.catch_up_policy.get().to::<u8>() {
+        CATCH_UP_ALL => due,
+        CATCH_UP_MAX_N => due.min(beneficiary.catch_up_max.get()),
+        _ => U256::from(1),
+    };
+
+    let mut periods = match amount {
+        U256::ZERO => owed,
+        _ => owed.min(balance / amount),
+    };
+    let max_executions = beneficiary.max_executions.get();
+    if max_executions != U256::ZERO {
+        periods = periods.min(max_executions.saturating_sub(beneficiary.executions.get()));
+    }
+    if periods == U256::ZERO {
+        return Err(insufficient());
+    }
+
+    let paid_through = advance_periods(beneficiary, first_due, due - owed + periods - U256::from(1));
+    Ok((periods, paid_through))
+}
+
+/// Number of due times from `first_due` up to and including `horizon`.
+fn count_due_periods(beneficiary: &Beneficiary, first_due: U256, horizon: U256) -> U256 {
+    if first_due > horizon {
+        return U256::ZERO;
+    }
+    if let Some(period) = fixed_period(beneficiary) {
+        return (horizon - first_due) / period + U256::from(1);
+    }
+    let mut count = U256::from(1);
+    let mut due = due_after(beneficiary, first_due);
+    while due <= horizon {
+        count += U256::from(1);
+        due = due_after(beneficiary, due);
+    }
+    count
+}
+
+/// The due time `periods` periods after the due time `from`.
+fn advance_periods(beneficiary: &Beneficiary, from: U256, periods: U256) -> U256 {
+    if let Some(period) = fixed_period(beneficiary) {
+        return from + period * periods;
+    }
+    let mut due = from;
+    for _ in 0..periods.to::<u64>() {
+        due = due_after(beneficiary, due);
+    }
+    due
+}
+
+/// Whether the beneficiary pays on a schedule rather than manually.
+fn is_automatic(beneficiary: &Beneficiary) -> bool {
+    beneficiary.frequency.get() != U256::ZERO || beneficiary.interval_seconds.get() != U256::ZERO
+}
+
+fn is_calendar(beneficiary: &Beneficiary) -> bool {
+    let frequency = u64::try_from(beneficiary.frequency.get());
+    beneficiary.interval_seconds.get() == U256::ZERO && matches!(frequency, Ok(FREQUENCY_MONTHLY | FREQUENCY_YEARLY))
+}
+
+/// Whether the beneficiary's end date has passed.
+fn schedule_ended(beneficiary: &Beneficiary, now: U256) -> bool {
+    let end_time = beneficiary.end_time.get();
+    end_time != U256::ZERO && now > end_time
+}
+
+fn is_leap_year(year: u64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: u64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts a Unix timestamp to `(year, month, day)`; Howard Hinnant's `civil_from_days`.
+fn civil_from_timestamp(timestamp: u64) -> (u64, u32, u32) {
+    let z = timestamp / 86400 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since the Unix epoch for a date on or after 1970-01-01; inverse of `civil_from_timestamp`.
+fn days_from_civil(year: u64, month: u32, day: u32) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = u64::from(if month > 2 { month - 3 } else { month + 9 });
+    let doy = (153 * mp + 2) / 5 + u64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -2,95 +2,27 @@
 
 extern crate alloc;
 use alloc::{vec};
-use core::cell::RefCell;
-use alloc::collections::BTreeMap;
 
-use stylus_sdk::{alloy_primitives::{address, Address, U256}, testing::*};
-use remittance_protocol::{UniversalRemittance, RemittanceErrors}; // adjust path if needed
+use stylus_sdk::{
+    abi::Bytes,
+    alloy_primitives::{address, Address, Bytes as AlloyBytes, B256, U256},
+    alloy_sol_types::{sol_data, Eip712Domain, SolStruct, SolType},
+    testing::*,
+};
+use remittance_protocol::{UniversalRemittance, RemittanceErrors, BATCH_STATUS_OK}; // adjust path if needed
 
 // -----------------------------
-// Mock ERC20 and improved in-memory token registry (test-only)
+// ERC20 call encoding and VM mocks (test-only)
 // -----------------------------
-#[derive(Default, Debug, Clone)]
-struct MockERC20 {
-    balances: BTreeMap<Address, U256>,
-    allowances: BTreeMap<(Address, Address), U256>,
-    self_addr: Address,
-}
-impl MockERC20 {
-    pub fn deployed_at(self_addr: Address) -> Self {
-        Self {
-            self_addr,
-            ..Default::default()
-        }
-    }
-    pub fn mint(&mut self, to: Address, amount: U256) {
-        let entry = self.balances.entry(to).or_insert(U256::ZERO);
-        *entry = *entry + amount;
-    }
-    pub fn approve(&mut self, owner: Address, spender: Address, amount: U256) {
-        self.allowances.insert((owner, spender), amount);
-    }
-    pub fn balance_of(&self, acct: Address) -> U256 {
-        *self.balances.get(&acct).unwrap_or(&U256::ZERO)
-    }
-    #[allow(dead_code)]
-    pub fn transfer(&mut self, from: Address, to: Address, amount: U256) -> bool {
-        let fb = self.balance_of(from);
-        if fb < amount {
-            return false;
-        }
-        self.balances.insert(from, fb - amount);
-        let tb = self.balance_of(to);
-        self.balances.insert(to, tb + amount);
-        true
-    }
-    #[allow(dead_code)]
-    pub fn transfer_from(&mut self, spender: Address, owner: Address, to: Address, amount: U256) -> bool {
-        let allow = *self.allowances.get(&(owner, spender)).unwrap_or(&U256::ZERO);
-        if allow < amount {
-            return false;
-        }
-        if !self.transfer(owner, to, amount) {
-            return false;
-        }
-        self.allowances.insert((owner, spender), allow - amount);
-        true
-    }
-}
-
-// Single-layer RefCell map (avoids nested RefCell borrow complexity)
-thread_local! {
-    static TOKENS: RefCell<BTreeMap<Address, MockERC20>> = RefCell::new(BTreeMap::new());
-}
-
-/// Insert the mock token into registry and return its address.
-fn put_token(token: MockERC20) -> Address {
-    let addr = token.self_addr;
-    TOKENS.with(|m| {
-        let mut map = m.borrow_mut();
-        map.insert(addr, token);
-    });
-    addr
-}
-/// Seed a token balance and approve for a spender. Safe if token wasn't present before.
-fn seed_token_balance_and_approve(token: Address, owner: Address, spender: Address, amount: U256) {
-    TOKENS.with(|m| {
-        let mut map = m.borrow_mut();
-        let t = map.entry(token).or_insert_with(|| MockERC20::deployed_at(token));
-        t.mint(owner, amount);
-        t.approve(owner, spender, amount);
-    });
-}
 
 // Helper function to encode ERC20 function calls
 fn encode_transfer_from(from: Address, to: Address, amount: U256) -> Vec<u8> {
     // transferFrom(address,address,uint256) selector: 0x23b872dd
-    let mut data = vec![0x23, 0x87, 0x2d, 0xd];
-    data.extend_from_slice(&from.as_slice());
+    let mut data = vec![0x23, 0xb8, 0x72, 0xdd];
     data.extend_from_slice(&[0u8; 12]); // padding
-    data.extend_from_slice(&to.as_slice());
+    data.extend_from_slice(from.as_slice());
     data.extend_from_slice(&[0u8; 12]); // padding
+    data.extend_from_slice(to.as_slice());
     data.extend_from_slice(&amount.to_be_bytes::<32>());
     data
 }
@@ -98,8 +30,8 @@ fn encode_transfer_from(from: Address, to: Address, amount: U256) -> Vec<u8> {
 fn encode_transfer(to: Address, amount: U256) -> Vec<u8> {
     // transfer(address,uint256) selector: 0xa9059cbb
     let mut data = vec![0xa9, 0x05, 0x9c, 0xbb];
-    data.extend_from_slice(&to.as_slice());
     data.extend_from_slice(&[0u8; 12]); // padding
+    data.extend_from_slice(to.as_slice());
     data.extend_from_slice(&amount.to_be_bytes::<32>());
     data
 }
@@ -107,8 +39,25 @@ fn encode_transfer(to: Address, amount: U256) -> Vec<u8> {
 fn encode_balance_of(account: Address) -> Vec<u8> {
     // balanceOf(address) selector: 0x70a08231
     let mut data = vec![0x70, 0xa0, 0x82, 0x31];
-    data.extend_from_slice(&account.as_slice());
     data.extend_from_slice(&[0u8; 12]); // padding
+    data.extend_from_slice(account.as_slice());
+    data
+}
+
+#[allow(clippy::too_many_arguments)]
+fn encode_permit(owner: Address, spender: Address, value: U256, deadline: U256, v: u8, r: B256, s: B256) -> Vec<u8> {
+    // EIP-2612 permit(address,address,uint256,uint256,uint8,bytes32,bytes32) selector: 0xd505accf
+    let mut data = vec![0xd5, 0x05, 0xac, 0xcf];
+    data.extend_from_slice(&[0u8; 12]); // padding
+    data.extend_from_slice(owner.as_slice());
+    data.extend_from_slice(&[0u8; 12]); // padding
+    data.extend_from_slice(spender.as_slice());
+    data.extend_from_slice(&value.to_be_bytes::<32>());
+    data.extend_from_slice(&deadline.to_be_bytes::<32>());
+    data.extend_from_slice(&[0u8; 31]); // padding
+    data.push(v);
+    data.extend_from_slice(r.as_slice());
+    data.extend_from_slice(s.as_slice());
     data
 }
 
@@ -123,12 +72,78 @@ fn encode_uint256(value: U256) -> Vec<u8> {
     value.to_be_bytes::<32>().to_vec()
 }
 
-// Helpers to simulate IERC20 behavior used by the contract
-// In the Stylus test environment the contract will call out to the token address.
-// We'll intercept those calls by providing functions tests call directly to mutate the registry
-// so the contract's expected state matches test assumptions.
-// (These helpers are used to seed and assert; the contract uses its IERC20 external calls
-// which - for stylus tests - are simulated by the test framework; we ensure balances/allowances are set correctly.)
+// The TestVM answers unmocked calls with empty return data, which the contract
+// treats as a failed transfer because the mocked tokens have no code. Tests
+// therefore mock every token call they expect to succeed, and a passing call
+// proves the exact recipient and amount.
+fn mock_transfer(vm: &TestVM, token: Address, to: Address, amount: U256) {
+    vm.mock_call(token, encode_transfer(to, amount), Ok(encode_bool_true()));
+}
+
+fn mock_transfer_from(vm: &TestVM, token: Address, from: Address, to: Address, amount: U256) {
+    vm.mock_call(token, encode_transfer_from(from, to, amount), Ok(encode_bool_true()));
+}
+
+fn mock_balance_of(vm: &TestVM, token: Address, account: Address, balance: U256) {
+    vm.mock_static_call(token, encode_balance_of(account), Ok(encode_uint256(balance)));
+}
+
+fn mock_transfer_revert(vm: &TestVM, token: Address, to: Address, amount: U256) {
+    vm.mock_call(token, encode_transfer(to, amount), Err(vec![]));
+}
+
+fn encode_is_valid_signature(hash: B256, signature: &[u8]) -> Vec<u8> {
+    // isValidSignature(bytes32,bytes) selector: 0x1626ba7e
+    let mut data = vec![0x16, 0x26, 0xba, 0x7e];
+    data.extend_from_slice(hash.as_slice());
+    data.extend_from_slice(&U256::from(64u64).to_be_bytes::<32>()); // offset of signature
+    data.extend_from_slice(&U256::from(signature.len()).to_be_bytes::<32>());
+    data.extend_from_slice(signature);
+    data.resize(data.len() + (32 - signature.len() % 32) % 32, 0); // padding
+    data
+}
+
+// A stand-in ERC-1271 wallet at `wallet`: it has code, and answers
+// `isValidSignature(hash, signature)` with the magic value when `accept` is set.
+fn deploy_mock_wallet(vm: &TestVM, wallet: Address, hash: B256, signature: &[u8], accept: bool) {
+    vm.set_code(wallet, vec![0xfe]);
+    let mut magic = vec![0u8; 32];
+    if accept {
+        magic[..4].copy_from_slice(&[0x16, 0x26, 0xba, 0x7e]);
+    }
+    vm.mock_static_call(wallet, encode_is_valid_signature(hash, signature), Ok(magic));
+}
+
+// Wraps an ABI-encoded intent struct as `abi.encode(uint8 kind, bytes data)`.
+fn encode_intent(kind: u8, data: Vec<u8>) -> Bytes {
+    Bytes(<(sol_data::Uint<8>, sol_data::Bytes) as SolType>::abi_encode_params(&(kind, AlloyBytes::from(data))))
+}
+
+// Makes the ecrecover precompile attribute `signature` over `hash` to `signer`.
+fn mock_ecrecover(vm: &TestVM, hash: B256, signature: &[u8], signer: Address) {
+    let mut input = hash.to_vec();
+    input.extend_from_slice(&[0u8; 31]);
+    input.push(signature[64]);
+    input.extend_from_slice(&signature[..64]);
+    let mut output = vec![0u8; 12];
+    output.extend_from_slice(signer.as_slice());
+    vm.mock_static_call(address!("0x0000000000000000000000000000000000000001"), input, Ok(output));
+}
+
+// Signs a digest with a real private key, returning the signer's address and its
+// 65-byte `r || s || v` signature together with the address `ecrecover` yields for it
+fn sign_digest(private_key: &str, digest: B256) -> (Address, Vec<u8>, Address) {
+    use ethers::{signers::{LocalWallet, Signer}, types::H256};
+
+    let wallet: LocalWallet = private_key.parse().unwrap();
+    let signature = wallet.sign_hash(H256::from(digest.0)).unwrap();
+    let recovered = signature.recover(H256::from(digest.0)).unwrap();
+    (
+        Address::from(wallet.address().0),
+        signature.to_vec(),
+        Address::from(recovered.0),
+    )
+}
 
 // -----------------------------
 // Tests
@@ -144,7 +159,7 @@ fn constructor_and_defaults() {
     let treasury = address!("0x2000000000000000000000000000000000000002");
 
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
     let (payment_count, exec_count, fee_bps, paused, tre) = c.get_contract_stats();
     assert_eq!(payment_count, U256::ZERO);
@@ -160,6 +175,84 @@ fn constructor_and_defaults() {
     assert!(c.is_token_supported(usdt));
 }
 
+#[test]
+fn constructor_takes_explicit_owner_and_two_step_handover() {
+    use remittance_protocol::{
+        COMPLIANCE_ROLE, FEE_MANAGER_ROLE, LIMIT_MANAGER_ROLE, PAUSER_ROLE, TOKEN_MANAGER_ROLE, TREASURY_ADMIN_ROLE,
+    };
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    let factory = address!("0xFAC7000000000000000000000000000000000000");
+    let deployer_eoa = address!("0xE0A0000000000000000000000000000000000000");
+    let successor = address!("0x5CC0000000000000000000000000000000000000");
+
+    // Deployed through a factory: neither the caller nor tx.origin becomes owner
+    vm.set_sender(factory);
+    vm.set_tx_origin(deployer_eoa);
+    let err = c.constructor(Address::ZERO, treasury).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+    c.constructor(owner, treasury).unwrap();
+    assert_eq!(c.owner(), owner);
+    assert!(c.has_role(PAUSER_ROLE, owner));
+    assert!(!c.has_role(PAUSER_ROLE, factory));
+
+    // Only the owner can start a handover
+    vm.set_sender(successor);
+    assert!(matches!(c.transfer_ownership(successor), Err(RemittanceErrors::Unauthorized(_))));
+    vm.set_sender(owner);
+    c.transfer_ownership(successor).unwrap();
+    assert_eq!(c.pending_owner(), successor);
+    assert_eq!(c.owner(), owner);
+
+    // Only the nominee can accept it
+    vm.set_sender(factory);
+    assert!(matches!(c.accept_ownership(), Err(RemittanceErrors::Unauthorized(_))));
+    vm.set_sender(successor);
+    c.accept_ownership().unwrap();
+    assert_eq!(c.owner(), successor);
+    assert_eq!(c.pending_owner(), Address::ZERO);
+
+    // Every operational role moves from the old owner to the new one
+    let roles = [PAUSER_ROLE, FEE_MANAGER_ROLE, TOKEN_MANAGER_ROLE, LIMIT_MANAGER_ROLE, COMPLIANCE_ROLE, TREASURY_ADMIN_ROLE];
+    for role in roles {
+        assert!(!c.has_role(role, owner));
+        assert!(c.has_role(role, successor));
+    }
+    vm.set_sender(owner);
+    assert!(matches!(c.pause(), Err(RemittanceErrors::Unauthorized(_))));
+
+    // The new owner administers roles; the old one no longer can
+    vm.set_sender(successor);
+    c.revoke_role(PAUSER_ROLE, successor).unwrap();
+    c.grant_role(PAUSER_ROLE, successor).unwrap();
+    vm.set_sender(owner);
+    assert!(matches!(c.revoke_role(PAUSER_ROLE, successor), Err(RemittanceErrors::Unauthorized(_))));
+    assert!(matches!(c.transfer_ownership(owner), Err(RemittanceErrors::Unauthorized(_))));
+
+    // Renouncing leaves no owner and clears any pending nominee
+    vm.set_sender(successor);
+    c.grant_role(PAUSER_ROLE, factory).unwrap();
+    c.transfer_ownership(factory).unwrap();
+    c.renounce_ownership().unwrap();
+    assert_eq!(c.owner(), Address::ZERO);
+    assert_eq!(c.pending_owner(), Address::ZERO);
+    vm.set_sender(factory);
+    assert!(matches!(c.accept_ownership(), Err(RemittanceErrors::Unauthorized(_))));
+
+    // The old owner's roles go with it, while other role holders keep theirs
+    for role in roles {
+        assert!(!c.has_role(role, successor));
+    }
+    vm.set_sender(successor);
+    assert!(matches!(c.pause(), Err(RemittanceErrors::Unauthorized(_))));
+    vm.set_sender(factory);
+    c.pause().unwrap();
+}
+
 #[test]
 fn user_registration_and_double_register() {
     let vm = TestVM::default();
@@ -167,7 +260,7 @@ fn user_registration_and_double_register() {
 
     let owner = address!("0x1000000000000000000000000000000000000001");
     vm.set_sender(owner);
-    c.constructor(address!("0x2000000000000000000000000000000000000002")).unwrap();
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
 
     let alice = address!("0xA11CE00000000000000000000000000000000000");
 
@@ -271,7 +364,7 @@ fn deposit_withdraw_flow_and_insufficient_balance() {
     let contract_addr = c.vm().contract_address();
 
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
     vm.set_sender(alice);
     c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
@@ -330,224 +423,1535 @@ fn deposit_withdraw_flow_and_insufficient_balance() {
 }
 
 #[test]
-fn manual_payment_happy_and_fee_flow() {
+fn deposit_for_credits_another_user_and_records_funder() {
+    use remittance_protocol::BalanceDeposited;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    vm.set_sender(owner);
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
+
+    let parent = address!("0xA11CE00000000000000000000000000000000000");
+    let child = address!("0xC0DE000000000000000000000000000000000000");
+    let stranger = address!("0x5700000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    vm.set_sender(parent);
+    c.register_user("Mum".into(), "NG".into(), "0803".into()).unwrap();
+
+    // The funder does not need to be registered
+    vm.set_sender(child);
+    mock_transfer_from(&vm, usdc, child, c.vm().contract_address(), U256::from(750u64));
+    c.deposit_for(parent, usdc, U256::from(750u64)).unwrap();
+    assert_eq!(c.get_user_balance(parent, usdc), U256::from(750u64));
+    assert_eq!(c.get_user_balance(child, usdc), U256::ZERO);
+
+    let (topics, data) = vm.get_emitted_logs().last().cloned().unwrap();
+    assert_eq!(topics[0], BalanceDeposited::SIGNATURE_HASH);
+    assert_eq!(Address::from_word(topics[1]), parent);
+    assert_eq!(Address::from_word(topics[2]), child);
+    assert_eq!(BalanceDeposited::abi_decode_data(&data, true).unwrap(), (usdc, U256::from(750u64)));
+
+    let (sender, recipient, amount, token, _, payment_type, _, _) = c.get_payment(U256::ZERO).unwrap();
+    assert_eq!((sender, recipient, amount, token), (child, parent, U256::from(750u64), usdc));
+    assert_eq!(payment_type, U256::from(4u64));
+
+    // Only registered users can be credited
+    let err = c.deposit_for(stranger, usdc, U256::from(1u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::NotRegistered(_)));
+}
+
+#[test]
+fn withdraw_to_respects_allowlist_cooling_off() {
     let vm = TestVM::default();
     let mut c = UniversalRemittance::from(&vm);
 
     let owner = address!("0x1000000000000000000000000000000000000001");
-    let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
 
     let alice = address!("0xA11CE00000000000000000000000000000000000");
-    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let exchange = address!("0xE8C0000000000000000000000000000000000000");
+    let cold = address!("0xC01D000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    vm.set_block_timestamp(1_000);
     vm.set_sender(alice);
     c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
-    vm.set_sender(bob);
-    c.register_user("Bob".into(), "US".into(), "000".into()).unwrap();
+    mock_transfer_from(&vm, usdc, alice, c.vm().contract_address(), U256::from(1_000u64));
+    c.deposit_balance(usdc, U256::from(1_000u64)).unwrap();
+
+    // Without an allowlist any destination works
+    mock_transfer(&vm, usdc, exchange, U256::from(100u64));
+    c.withdraw_to(usdc, U256::from(100u64), exchange).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(900u64));
+
+    // With it on, a new destination waits out the cooling-off period
+    c.set_withdrawal_allowlist(true).unwrap();
+    c.add_withdrawal_destination(cold).unwrap();
+    assert_eq!(c.get_withdrawal_destination(alice, cold), U256::from(1_000u64 + 86_400));
+    mock_transfer(&vm, usdc, cold, U256::from(100u64));
+    let err = c.withdraw_to(usdc, U256::from(100u64), cold).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::DestinationNotAllowed(_)));
+    let err = c.withdraw_to(usdc, U256::from(100u64), exchange).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::DestinationNotAllowed(_)));
+
+    vm.set_block_timestamp(1_000 + 86_400);
+    c.withdraw_to(usdc, U256::from(100u64), cold).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(800u64));
+
+    // Switching the allowlist off is delayed too
+    c.set_withdrawal_allowlist(false).unwrap();
+    assert_eq!(c.get_withdrawal_allowlist(alice), (true, U256::from(1_000u64 + 2 * 86_400)));
+    let err = c.withdraw_to(usdc, U256::from(100u64), exchange).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::DestinationNotAllowed(_)));
+    vm.set_block_timestamp(1_000 + 2 * 86_400);
+    c.withdraw_to(usdc, U256::from(100u64), exchange).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(700u64));
+}
 
-    // token & support
-    let token = put_token(MockERC20::deployed_at(address!("0xBBB0000000000000000000000000000000000000")));
+#[test]
+fn allowlist_covers_every_payout_from_the_balance() {
+    use remittance_protocol::{SendPayment, Withdraw, INTENT_SEND_PAYMENT, INTENT_WITHDRAW};
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.add_supported_token(token).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
-    // Seed alice balance and approve contract
-let contract_addr = c.vm().contract_address();
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let relayer = address!("0xE1A7000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    let contract_addr = c.vm().contract_address();
+    vm.set_block_timestamp(1_000);
+    vm.set_sender(bob);
+    c.register_user("Bob".into(), "GH".into(), "0244".into()).unwrap();
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+    mock_transfer_from(&vm, usdc, alice, contract_addr, U256::from(1_000u64));
+    c.deposit_balance(usdc, U256::from(1_000u64)).unwrap();
+
+    // Bob is listed but still cooling off
+    c.set_withdrawal_allowlist(true).unwrap();
+    c.add_withdrawal_destination(bob).unwrap();
+    let not_allowed = |err: RemittanceErrors| assert!(matches!(err, RemittanceErrors::DestinationNotAllowed(_)));
+
+    mock_transfer(&vm, usdc, bob, U256::from(100u64));
+    not_allowed(c.send_payment_from_balance(bob, U256::from(100u64), usdc, "".into()).unwrap_err());
+    not_allowed(c.internal_transfer(bob, usdc, U256::from(100u64), "".into()).unwrap_err());
+    not_allowed(c.schedule_payment(bob, U256::from(100u64), usdc, U256::from(5_000u64), "".into()).unwrap_err());
+    not_allowed(c.add_beneficiary(bob, "Bob".into(), "Brother".into(), U256::from(100u64), usdc, U256::from(1u64)).unwrap_err());
+    let err = c.execute_auto_payments(alice, U256::ZERO).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::BeneficiaryNotFound(_)));
+
+    // Signed intents cannot pay an unlisted recipient or relayer either
+    let domain = Eip712Domain::new(
+        Some("UniversalRemittance".into()),
+        Some("1".into()),
+        Some(U256::from(c.vm().chain_id())),
+        Some(contract_addr),
+        None,
+    );
+    let mut signature = vec![0x11u8; 64];
+    signature.push(27);
+    let payment = SendPayment {
+        signer: alice,
+        recipient: bob,
+        amount: U256::from(100u64),
+        token: usdc,
+        note: "".into(),
+        feeToken: usdc,
+        relayerFee: U256::ZERO,
+        nonce: c.get_intent_nonce(alice),
+        deadline: U256::from(2_000u64),
+    };
+    mock_ecrecover(&vm, payment.eip712_signing_hash(&domain), &signature, alice);
+    vm.set_sender(relayer);
+    let intent = encode_intent(INTENT_SEND_PAYMENT, <SendPayment as SolType>::abi_encode(&payment));
+    not_allowed(c.execute_signed(intent, Bytes(signature.clone())).unwrap_err());
+
+    let withdraw = Withdraw {
+        signer: alice,
+        token: usdc,
+        amount: U256::from(100u64),
+        feeToken: usdc,
+        relayerFee: U256::from(900u64),
+        nonce: c.get_intent_nonce(alice),
+        deadline: U256::from(2_000u64),
+    };
+    mock_ecrecover(&vm, withdraw.eip712_signing_hash(&domain), &signature, alice);
+    mock_transfer(&vm, usdc, alice, U256::from(100u64));
+    mock_transfer(&vm, usdc, relayer, U256::from(900u64));
+    let intent = encode_intent(INTENT_WITHDRAW, <Withdraw as SolType>::abi_encode(&withdraw));
+    not_allowed(c.execute_signed(intent, Bytes(signature)).unwrap_err());
+
+    // Once the cooling-off period is over Bob can be paid
+    vm.set_block_timestamp(1_000 + 86_400);
+    vm.set_sender(alice);
+    c.send_payment_from_balance(bob, U256::from(100u64), usdc, "".into()).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(900u64));
+}
 
-    seed_token_balance_and_approve(token, alice, contract_addr, U256::from(1_000u64));
+#[test]
+fn native_eth_flows_through_balances_payments_and_schedules() {
+    use remittance_protocol::NATIVE_TOKEN;
 
-    // ensure contract has no tokens initially (we'll let transferFrom move tokens into it via mock)
-    TOKENS.with(|m| {
-        let map = m.borrow();
-        let t = map.get(&token).unwrap();
-        // sanity checks
-        assert_eq!(t.balance_of(alice), U256::from(1_000u64));
-    });
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
 
-    // Also ensure contract has tokens to transfer net amount and fee out when send_payment executes.
-    // The contract implementation does transfer_from(sender -> contract) then transfer(contract->recipient), transfer(contract->treasury)
-    // Our mock transfer_from moves tokens from alice to contract and reduces allowance.
-    // So once transfer_from runs, the contract will have tokens to do the subsequent transfers.
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+    c.add_supported_token(NATIVE_TOKEN).unwrap();
 
-    // Call send_payment as alice
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let contract_addr = c.vm().contract_address();
     vm.set_sender(alice);
-    c.send_payment(bob, U256::from(100u64), token, "Rent".into()).unwrap();
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
 
-    // Payment record at id 0
-    let (sender, recipient, amount, tok, _ts, payment_type, note, completed) = c.get_payment(U256::ZERO).unwrap();
-    assert_eq!(sender, alice);
-    assert_eq!(recipient, bob);
-    assert_eq!(amount, U256::from(100u64));
-    assert_eq!(tok, token);
-    assert_eq!(payment_type, U256::ZERO); // manual
-    assert_eq!(note, "Rent");
-    assert!(completed);
+    // Deposits are paid with the attached value, which must match exactly. The
+    // TestVM does not move attached value, so the contract balance is set by hand.
+    vm.set_value(U256::from(9_000u64));
+    let err = c.deposit_balance(NATIVE_TOKEN, U256::from(10_000u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidAmount(_)));
+    vm.set_value(U256::from(10_000u64));
+    c.deposit_balance(NATIVE_TOKEN, U256::from(10_000u64)).unwrap();
+    vm.set_balance(contract_addr, U256::from(10_000u64));
+    assert_eq!(c.get_user_balance(alice, NATIVE_TOKEN), U256::from(10_000u64));
+
+    // Value sent alongside an ERC20 payment is refused
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    mock_transfer_from(&vm, usdc, alice, contract_addr, U256::from(10_000u64));
+    let err = c.send_payment(bob, U256::from(10_000u64), usdc, "".into(), B256::ZERO).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidAmount(_)));
+
+    // A manual ETH payment, with the fee paid to the treasury in ETH
+    vm.set_balance(contract_addr, U256::from(20_000u64));
+    c.send_payment(bob, U256::from(10_000u64), NATIVE_TOKEN, "".into(), B256::ZERO).unwrap();
+    assert_eq!(c.vm().balance(bob), U256::from(9_950u64));
+    assert_eq!(c.vm().balance(treasury), U256::from(50u64));
+    vm.set_value(U256::ZERO);
+
+    // A weekly ETH schedule paid from the internal balance
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(2_000u64), NATIVE_TOKEN, U256::from(7u64)).unwrap();
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    assert_eq!(c.get_user_balance(alice, NATIVE_TOKEN), U256::from(8_000u64));
+    assert_eq!(c.vm().balance(bob), U256::from(11_940u64));
+    assert_eq!(c.vm().balance(treasury), U256::from(60u64));
+
+    // Withdrawals send ETH back; the contract still covers what it owes
+    c.withdraw_balance(NATIVE_TOKEN, U256::from(3_000u64)).unwrap();
+    assert_eq!(c.vm().balance(alice), U256::from(3_000u64));
+    let (balance, liabilities, _, shortfall) = c.get_token_solvency(NATIVE_TOKEN).unwrap();
+    assert_eq!((balance, liabilities, shortfall), (U256::from(5_000u64), U256::from(5_000u64), U256::ZERO));
+}
 
-    // Treasury should have received fee (fee 0.5% of 100 = 0 (floor), but logic uses integer math).
-    // For larger amount test fee distribution: send 10_000 (1% = 100) etc.
-    // We'll do another payment to verify fee movement with bigger amount
+#[test]
+fn tolerates_tokens_that_return_no_data() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
 
-    // Seed more allowance/balance so next call works
-    seed_token_balance_and_approve(token, alice, contract_addr, U256::from(10_000u64));
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let legacy_usdt = address!("0x0DD0000000000000000000000000000000000000");
+    let no_code = address!("0xDEAD000000000000000000000000000000000000");
+    let contract_addr = c.vm().contract_address();
+    c.add_supported_token(legacy_usdt).unwrap();
+    c.add_supported_token(no_code).unwrap();
     vm.set_sender(alice);
-    c.send_payment(bob, U256::from(10_000u64), token, "Invoice".into()).unwrap();
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
 
-    // Now check balances in mock to ensure net + fee moved correctly
-    TOKENS.with(|m| {
-        let map = m.borrow();
-        let t = map.get(&token).unwrap();
-        // Contract address should have less net because transfers moved out
-        // The cumulative effects: initial mint 1000 then transferFrom 100 + 10000; contract then forwarded net and fee.
-        // Check recipient (bob) received net from both payments:
-        // First payment net = 100 - fee(0) = 100; second net = 10000 - fee (10000 * 50 / 10000 = 50) => 9950
-        let bob_balance = t.balance_of(bob);
-        assert!(bob_balance >= U256::from(100u64)); // at least the first payment
-        // treasury should have fee: 50
-        let treasury_balance = t.balance_of(treasury);
-        assert!(treasury_balance >= U256::from(50u64));
-    });
+    // A token with code that returns nothing is treated as a successful transfer
+    vm.set_code(legacy_usdt, vec![0xfe]);
+    vm.mock_call(legacy_usdt, encode_transfer_from(alice, contract_addr, U256::from(10_000u64)), Ok(vec![]));
+    vm.mock_call(legacy_usdt, encode_transfer(bob, U256::from(9_950u64)), Ok(vec![]));
+    vm.mock_call(legacy_usdt, encode_transfer(treasury, U256::from(50u64)), Ok(vec![]));
+    c.send_payment(bob, U256::from(10_000u64), legacy_usdt, "".into(), B256::ZERO).unwrap();
+
+    // An explicit `false` still fails
+    vm.mock_call(legacy_usdt, encode_transfer_from(alice, contract_addr, U256::from(500u64)), Ok(vec![0u8; 32]));
+    let err = c.deposit_balance(legacy_usdt, U256::from(500u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::TransferFailed(_)));
+
+    // So does an empty return from an address with no code behind it
+    vm.mock_call(no_code, encode_transfer_from(alice, contract_addr, U256::from(500u64)), Ok(vec![]));
+    let err = c.deposit_balance(no_code, U256::from(500u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::TransferFailed(_)));
+    assert_eq!(c.get_user_balance(alice, no_code), U256::ZERO);
 }
 
 #[test]
-fn beneficiary_add_update_remove_and_get_pending_estimate() {
+fn permit_replaces_separate_approval() {
     let vm = TestVM::default();
     let mut c = UniversalRemittance::from(&vm);
 
     let owner = address!("0x1000000000000000000000000000000000000001");
     let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
     let alice = address!("0xA11CE00000000000000000000000000000000000");
     let bob = address!("0xB0B0000000000000000000000000000000000000");
     vm.set_sender(alice);
     c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
-    vm.set_sender(bob);
-    c.register_user("Bob".into(), "GH".into(), "000".into()).unwrap();
 
-    let token = put_token(MockERC20::deployed_at(address!("0xCCC0000000000000000000000000000000000000")));
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    let contract_addr = c.vm().contract_address();
+    let deadline = U256::from(2_000u64);
+    let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+
+    // Permit then pull, in one call
+    let amount = U256::from(1_000u64);
+    vm.mock_call(usdc, encode_permit(alice, contract_addr, amount, deadline, v, r, s), Ok(vec![]));
+    mock_transfer_from(&vm, usdc, alice, contract_addr, amount);
+    c.deposit_with_permit(usdc, amount, deadline, v, r, s).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), amount);
+
+    // A permit someone already submitted reverts, but the allowance it granted still works
+    let amount = U256::from(2_000u64);
+    vm.mock_call(usdc, encode_permit(alice, contract_addr, amount, deadline, v, r, s), Err(vec![]));
+    mock_transfer_from(&vm, usdc, alice, contract_addr, amount);
+    mock_transfer(&vm, usdc, bob, U256::from(1_990u64));
+    mock_transfer(&vm, usdc, treasury, U256::from(10u64));
+    c.send_payment_with_permit(bob, amount, usdc, "gift".into(), B256::ZERO, deadline, v, r, s).unwrap();
+    let (payment_count, _, _, _, _) = c.get_contract_stats();
+    assert_eq!(payment_count, U256::from(1u64));
+
+    // Without an allowance the pull still fails
+    let amount = U256::from(3_000u64);
+    vm.mock_call(usdc, encode_permit(alice, contract_addr, amount, deadline, v, r, s), Err(vec![]));
+    let err = c.deposit_with_permit(usdc, amount, deadline, v, r, s).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::TransferFailed(_)));
+}
+
+#[test]
+fn relayer_executes_signed_intents() {
+    use remittance_protocol::{RegisterUser, SendPayment, INTENT_REGISTER_USER, INTENT_SEND_PAYMENT};
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.add_supported_token(token).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
-    // Add beneficiary for alice
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let relayer = address!("0xE1A7000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    let contract_addr = c.vm().contract_address();
+    vm.set_block_timestamp(1_000);
+
+    let domain = Eip712Domain::new(
+        Some("UniversalRemittance".into()),
+        Some("1".into()),
+        Some(U256::from(c.vm().chain_id())),
+        Some(contract_addr),
+        None,
+    );
+    assert_eq!(c.domain_separator(), domain.separator());
+    let mut signature = vec![0x11u8; 64];
+    signature.push(27);
+
+    // Alice registers through the relayer without paying gas
+    let register = RegisterUser {
+        signer: alice,
+        name: "Alice".into(),
+        country: "NG".into(),
+        phoneNumber: "0803".into(),
+        feeToken: Address::ZERO,
+        relayerFee: U256::ZERO,
+        nonce: U256::ZERO,
+        deadline: U256::from(2_000u64),
+    };
+    vm.set_sender(relayer);
+
+    // A new user has no balance to pay a relayer fee from, so registrations cannot carry one
+    let paid = RegisterUser { feeToken: usdc, relayerFee: U256::from(1u64), ..register.clone() };
+    mock_ecrecover(&vm, paid.eip712_signing_hash(&domain), &signature, alice);
+    let err = c
+        .execute_signed(encode_intent(INTENT_REGISTER_USER, <RegisterUser as SolType>::abi_encode(&paid)), Bytes(signature.clone()))
+        .unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+    assert_eq!(c.get_intent_nonce(alice), U256::ZERO);
+
+    mock_ecrecover(&vm, register.eip712_signing_hash(&domain), &signature, alice);
+    c.execute_signed(encode_intent(INTENT_REGISTER_USER, <RegisterUser as SolType>::abi_encode(&register)), Bytes(signature.clone())).unwrap();
+    let (name, _, _, _, _, _, _) = c.get_user_profile(alice);
+    assert_eq!(name, "Alice");
+    assert_eq!(c.get_intent_nonce(alice), U256::from(1u64));
+
+    // Fund the internal balance that both the payment and the relayer fee come from
     vm.set_sender(alice);
-    c.add_beneficiary(bob, "Bob".into(), "friend".into(), U256::from(200u64), token, U256::from(7u64)).unwrap();
+    mock_transfer_from(&vm, usdc, alice, contract_addr, U256::from(1_100u64));
+    c.deposit_balance(usdc, U256::from(1_100u64)).unwrap();
+
+    // A payment debited from Alice's balance, so she never needs an on-chain approval
+    let payment = SendPayment {
+        signer: alice,
+        recipient: bob,
+        amount: U256::from(1_000u64),
+        token: usdc,
+        note: "rent".into(),
+        feeToken: usdc,
+        relayerFee: U256::from(3u64),
+        nonce: U256::from(1u64),
+        deadline: U256::from(2_000u64),
+    };
+    let intent = encode_intent(INTENT_SEND_PAYMENT, <SendPayment as SolType>::abi_encode(&payment));
+    mock_ecrecover(&vm, payment.eip712_signing_hash(&domain), &signature, alice);
+    mock_transfer(&vm, usdc, bob, U256::from(995u64));
+    mock_transfer(&vm, usdc, treasury, U256::from(5u64));
+    mock_transfer(&vm, usdc, relayer, U256::from(3u64));
+    vm.set_sender(relayer);
+    c.execute_signed(intent.clone(), Bytes(signature.clone())).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(97u64));
+    let (sender, recipient, amount, _, _, _, note, _) = c.get_payment(U256::ZERO).unwrap();
+    assert_eq!((sender, recipient, amount, note.as_str()), (alice, bob, U256::from(1_000u64), "rent"));
+
+    // Replays are rejected
+    let err = c.execute_signed(intent, Bytes(signature.clone())).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidNonce(_)));
+
+    // So are intents signed by someone else, or past their deadline
+    let mallory = address!("0xBAD0000000000000000000000000000000000000");
+    let payment = SendPayment { nonce: U256::from(2u64), ..payment };
+    let intent = encode_intent(INTENT_SEND_PAYMENT, <SendPayment as SolType>::abi_encode(&payment));
+    mock_ecrecover(&vm, payment.eip712_signing_hash(&domain), &signature, mallory);
+    let err = c.execute_signed(intent.clone(), Bytes(signature.clone())).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidSignature(_)));
+
+    vm.set_block_timestamp(2_001);
+    let err = c.execute_signed(intent, Bytes(signature)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::IntentExpired(_)));
+    assert_eq!(c.get_intent_nonce(alice), U256::from(2u64));
+}
 
-    let (addr, name, rel, amount, tok, freq, last_payment, active, total_sent) = c.get_beneficiary(alice, U256::ZERO).unwrap();
-    assert_eq!(addr, bob);
-    assert_eq!(name, "Bob");
-    assert_eq!(rel, "friend");
-    assert_eq!(amount, U256::from(200u64));
-    assert_eq!(tok, token);
-    assert_eq!(freq, U256::from(7u64));
-    assert_eq!(last_payment, U256::ZERO);
-    assert!(active);
-    assert_eq!(total_sent, U256::ZERO);
+#[test]
+fn signed_intent_verifies_a_real_ecdsa_signature() {
+    use remittance_protocol::{RegisterUser, INTENT_REGISTER_USER};
 
-    // Update beneficiary
-    vm.set_sender(alice);
-    c.update_beneficiary(U256::ZERO, U256::from(300u64), U256::from(30u64)).unwrap();
-    let (_, _, _, amount2, _, freq2, _, _, _) = c.get_beneficiary(alice, U256::ZERO).unwrap();
-    assert_eq!(amount2, U256::from(300u64));
-    assert_eq!(freq2, U256::from(30u64));
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
 
-    // get_pending_auto_payments -> none because no balance
-    let pending = c.get_pending_auto_payments(alice);
-    assert!(pending.is_empty());
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    vm.set_sender(owner);
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
+
+    // The well-known address of private key 1
+    let alice = address!("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+    let relayer = address!("0xE1A7000000000000000000000000000000000000");
+    let domain = Eip712Domain::new(
+        Some("UniversalRemittance".into()),
+        Some("1".into()),
+        Some(U256::from(c.vm().chain_id())),
+        Some(c.vm().contract_address()),
+        None,
+    );
+    assert_eq!(c.domain_separator(), domain.separator());
+
+    let register = RegisterUser {
+        signer: alice,
+        name: "Alice".into(),
+        country: "NG".into(),
+        phoneNumber: "0803".into(),
+        feeToken: Address::ZERO,
+        relayerFee: U256::ZERO,
+        nonce: U256::ZERO,
+        deadline: U256::MAX,
+    };
+    let digest = register.eip712_signing_hash(&domain);
+    let (signer, signature, recovered) =
+        sign_digest("0000000000000000000000000000000000000000000000000000000000000001", digest);
+    assert_eq!(signer, alice);
+    assert_eq!(recovered, alice);
+
+    // The precompile answers only for the exact digest and signature the key produced
+    mock_ecrecover(&vm, digest, &signature, recovered);
+
+    // A different intent under the same signature recovers nobody
+    let forged = RegisterUser { name: "Mallory".into(), ..register.clone() };
+    vm.set_sender(relayer);
+    let err = c
+        .execute_signed(encode_intent(INTENT_REGISTER_USER, <RegisterUser as SolType>::abi_encode(&forged)), Bytes(signature.clone()))
+        .unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidSignature(_)));
+
+    c.execute_signed(encode_intent(INTENT_REGISTER_USER, <RegisterUser as SolType>::abi_encode(&register)), Bytes(signature)).unwrap();
+    let (name, _, _, _, _, _, _) = c.get_user_profile(alice);
+    assert_eq!(name, "Alice");
+    assert_eq!(c.get_intent_nonce(alice), U256::from(1u64));
+}
 
-    // Seed balance so pending appears
-let contract_addr = c.vm().contract_address();
-    seed_token_balance_and_approve(token, alice, contract_addr, U256::from(1_000u64));
-    vm.set_sender(alice);
-    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+#[test]
+fn smart_wallet_signs_intents_via_erc1271() {
+    use remittance_protocol::{RegisterUser, Withdraw, INTENT_REGISTER_USER, INTENT_WITHDRAW};
 
-    // Now pending should contain index 0 (because last_payment==0 and user has balance >= amount)
-    let pending2 = c.get_pending_auto_payments(alice);
-    assert_eq!(pending2.len(), 1);
-    assert_eq!(pending2[0], U256::from(0u64));
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
 
-    // estimate next payment time -> since last_payment == 0 should return current block timestamp (can execute now)
-    let est = c.estimate_next_payment_time(alice, U256::ZERO).unwrap();
-    assert!(est > U256::ZERO || est == U256::from(vm.block_timestamp())); // implementation returns block_timestamp if last_payment==0
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    vm.set_sender(owner);
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
 
-    // Remove beneficiary and ensure subsequent get fails
-    vm.set_sender(alice);
-    c.remove_beneficiary(U256::ZERO).unwrap();
-    let err = c.get_beneficiary(alice, U256::ZERO).unwrap_err();
-    match err {
-        RemittanceErrors::BeneficiaryNotFound(_) => {}
-        _ => panic!("expected BeneficiaryNotFound"),
-    }
+    let safe = address!("0x5AFE000000000000000000000000000000000000");
+    let relayer = address!("0xE1A7000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    let contract_addr = c.vm().contract_address();
+    let domain = Eip712Domain::new(
+        Some("UniversalRemittance".into()),
+        Some("1".into()),
+        Some(U256::from(c.vm().chain_id())),
+        Some(contract_addr),
+        None,
+    );
+    // Two concatenated owner signatures, as a 2-of-n Safe would pass them
+    let signature = vec![0x22u8; 130];
+
+    let register = RegisterUser {
+        signer: safe,
+        name: "Acme Ltd".into(),
+        country: "KE".into(),
+        phoneNumber: "0700".into(),
+        feeToken: Address::ZERO,
+        relayerFee: U256::ZERO,
+        nonce: U256::ZERO,
+        deadline: U256::MAX,
+    };
+    deploy_mock_wallet(&vm, safe, register.eip712_signing_hash(&domain), &signature, true);
+    vm.set_sender(relayer);
+    c.execute_signed(encode_intent(INTENT_REGISTER_USER, <RegisterUser as SolType>::abi_encode(&register)), Bytes(signature.clone())).unwrap();
+    assert_eq!(c.get_intent_nonce(safe), U256::from(1u64));
+
+    vm.set_sender(safe);
+    mock_transfer_from(&vm, usdc, safe, contract_addr, U256::from(500u64));
+    c.deposit_balance(usdc, U256::from(500u64)).unwrap();
+
+    // The wallet refusing the signature rejects the intent
+    let withdraw = Withdraw {
+        signer: safe,
+        token: usdc,
+        amount: U256::from(200u64),
+        feeToken: Address::ZERO,
+        relayerFee: U256::ZERO,
+        nonce: U256::from(1u64),
+        deadline: U256::MAX,
+    };
+    let intent = encode_intent(INTENT_WITHDRAW, <Withdraw as SolType>::abi_encode(&withdraw));
+    deploy_mock_wallet(&vm, safe, withdraw.eip712_signing_hash(&domain), &signature, false);
+    vm.set_sender(relayer);
+    let err = c.execute_signed(intent.clone(), Bytes(signature.clone())).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidSignature(_)));
+
+    deploy_mock_wallet(&vm, safe, withdraw.eip712_signing_hash(&domain), &signature, true);
+    mock_transfer(&vm, usdc, safe, U256::from(200u64));
+    c.execute_signed(intent, Bytes(signature)).unwrap();
+    assert_eq!(c.get_user_balance(safe, usdc), U256::from(300u64));
 }
 
 #[test]
-fn execute_auto_payment_and_frequency_lock() {
+fn manual_payment_happy_and_fee_flow() {
     let vm = TestVM::default();
     let mut c = UniversalRemittance::from(&vm);
 
     let owner = address!("0x1000000000000000000000000000000000000001");
     let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
     let alice = address!("0xA11CE00000000000000000000000000000000000");
     let bob = address!("0xB0B0000000000000000000000000000000000000");
     vm.set_sender(alice);
     c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
     vm.set_sender(bob);
-    c.register_user("Bob".into(), "GH".into(), "000".into()).unwrap();
+    c.register_user("Bob".into(), "US".into(), "000".into()).unwrap();
 
-    let token = put_token(MockERC20::deployed_at(address!("0xDDD0000000000000000000000000000000000000")));
+    // token & support
+    let token = address!("0xBBB0000000000000000000000000000000000000");
     vm.set_sender(owner);
     c.add_supported_token(token).unwrap();
 
-    // add beneficiary with daily frequency (1)
-    vm.set_sender(alice);
-    c.add_beneficiary(bob, "Bob".into(), "friend".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
-
-    // seed alice internal balance by deposit path
+    // The contract implementation does transfer_from(sender -> contract) then transfer(contract->recipient), transfer(contract->treasury)
+    // Fee on 100 at 0.5% floors to zero, so no treasury transfer is expected for the first payment.
     let contract_addr = c.vm().contract_address();
-    seed_token_balance_and_approve(token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(100u64));
+    mock_transfer(&vm, token, bob, U256::from(100u64));
+
+    // Call send_payment as alice
     vm.set_sender(alice);
-    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+    c.send_payment(bob, U256::from(100u64), token, "Rent".into(), B256::ZERO).unwrap();
 
-    // set block timestamp to known value
-    vm.set_block_timestamp(1000);
-    // execute
-    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    // Payment record at id 0
+    let (sender, recipient, amount, tok, _ts, payment_type, note, completed) = c.get_payment(U256::ZERO).unwrap();
+    assert_eq!(sender, alice);
+    assert_eq!(recipient, bob);
+    assert_eq!(amount, U256::from(100u64));
+    assert_eq!(tok, token);
+    assert_eq!(payment_type, U256::ZERO); // manual
+    assert_eq!(note, "Rent");
+    assert!(completed);
 
-    // beneficiary last_payment updated to 1000 and total_sent increased
-    let (_, _, _, _, _, _, last_payment, _, total_sent) = c.get_beneficiary(alice, U256::ZERO).unwrap();
-    assert_eq!(last_payment, U256::from(1000u64));
-    assert_eq!(total_sent, U256::from(50u64));
+    // Treasury should have received fee (fee 0.5% of 100 = 0 (floor), but logic uses integer math).
+    // For larger amount test fee distribution: send 10_000 (1% = 100) etc.
+    // We'll do another payment to verify fee movement with bigger amount
 
-    // trying to execute again immediately should fail with FrequencyNotMet
-    let err = c.execute_auto_payments(alice, U256::ZERO).unwrap_err();
-    match err {
-        RemittanceErrors::FrequencyNotMet(_) => {}
-        _ => panic!("expected FrequencyNotMet"),
-    }
+    // Second payment: net = 10000 - fee (10000 * 50 / 10000 = 50) => 9950 to bob, 50 to treasury.
+    // Only these exact transfers are mocked, so success proves the split.
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(10_000u64));
+    mock_transfer(&vm, token, bob, U256::from(9_950u64));
+    mock_transfer(&vm, token, treasury, U256::from(50u64));
+    vm.set_sender(alice);
+    c.send_payment(bob, U256::from(10_000u64), token, "Invoice".into(), B256::ZERO).unwrap();
 
-    // advance time by 2 days (2*86400)
-    vm.set_block_timestamp(1000 + 2 * 86400);
-    // Now should succeed again
-    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    let (_, _, _, _, total_sent, _, _) = c.get_user_profile(alice);
+    assert_eq!(total_sent, U256::from(10_100u64));
+    let (_, _, _, _, _, total_received, _) = c.get_user_profile(bob);
+    assert_eq!(total_received, U256::from(10_050u64));
 }
 
 #[test]
-fn batch_execute_auto_payments_returns_results() {
+fn payment_reference_rejects_retries() {
+    use remittance_protocol::PaymentSent;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
     let vm = TestVM::default();
     let mut c = UniversalRemittance::from(&vm);
 
     let owner = address!("0x1000000000000000000000000000000000000001");
-    let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
 
-    // create two users and their beneficiaries
     let alice = address!("0xA11CE00000000000000000000000000000000000");
     let bob = address!("0xB0B0000000000000000000000000000000000000");
-    let charlie = address!("0xC0C0000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    let contract_addr = c.vm().contract_address();
+    for user in [alice, bob] {
+        vm.set_sender(user);
+        c.register_user("User".into(), "NG".into(), "0803".into()).unwrap();
+    }
+
+    mock_transfer_from(&vm, usdc, alice, contract_addr, U256::from(100u64));
+    mock_transfer(&vm, usdc, bob, U256::from(100u64));
+    mock_transfer_from(&vm, usdc, bob, contract_addr, U256::from(100u64));
+    mock_transfer(&vm, usdc, alice, U256::from(100u64));
+
+    // Payments without a reference are never treated as retries
+    vm.set_sender(alice);
+    c.send_payment(bob, U256::from(100u64), usdc, "a".into(), B256::ZERO).unwrap();
+    c.send_payment(bob, U256::from(100u64), usdc, "b".into(), B256::ZERO).unwrap();
+
+    let reference = B256::repeat_byte(0x42);
+    c.send_payment(bob, U256::from(100u64), usdc, "c".into(), reference).unwrap();
+    let (_, data) = vm.get_emitted_logs().last().cloned().unwrap();
+    let event = PaymentSent::abi_decode_data(&data, true).unwrap();
+    assert_eq!(event.3, reference);
+
+    // A retry with the same reference is rejected
+    let err = c.send_payment(bob, U256::from(100u64), usdc, "c".into(), reference).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::DuplicateReference(_)));
+    assert_eq!(c.get_payment_by_reference(alice, reference).unwrap(), U256::from(2u64));
+    assert_eq!(c.get_payment_reference(U256::from(2u64)).unwrap(), reference);
+    assert_eq!(c.get_payment_reference(U256::ZERO).unwrap(), B256::ZERO);
+
+    // References are scoped to their sender
+    vm.set_sender(bob);
+    c.send_payment(alice, U256::from(100u64), usdc, "c".into(), reference).unwrap();
+    assert_eq!(c.get_payment_by_reference(bob, reference).unwrap(), U256::from(3u64));
+    assert!(c.get_payment_by_reference(alice, B256::repeat_byte(0x43)).is_err());
+}
+
+#[test]
+fn internal_transfer_moves_balance_without_token_calls() {
+    use remittance_protocol::InternalTransferFeeCharged;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let carol = address!("0xCA70000000000000000000000000000000000000");
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    for user in [alice, bob] {
+        vm.set_sender(user);
+        c.register_user("User".into(), "NG".into(), "0803".into()).unwrap();
+    }
+    vm.set_sender(alice);
+    mock_transfer_from(&vm, usdc, alice, c.vm().contract_address(), U256::from(10_000u64));
+    c.deposit_balance(usdc, U256::from(10_000u64)).unwrap();
+
+    // Free by default; no token call is mocked, so any transfer would fail
+    let payment_id = c.internal_transfer(bob, usdc, U256::from(4_000u64), "school fees".into()).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(6_000u64));
+    assert_eq!(c.get_user_balance(bob, usdc), U256::from(4_000u64));
+    let (sender, recipient, amount, _, _, payment_type, note, completed) = c.get_payment(payment_id).unwrap();
+    assert_eq!((sender, recipient, amount), (alice, bob, U256::from(4_000u64)));
+    assert_eq!(payment_type, U256::from(3u64));
+    assert_eq!(note, "school fees");
+    assert!(completed);
+
+    // The fee is timelocked like the platform fee and accrues in the fee ledger
+    vm.set_sender(owner);
+    let change_id = c.update_internal_transfer_fee(U256::from(25u64)).unwrap();
+    vm.set_block_timestamp(2 * 86400 + 1);
+    c.execute_governance_change(change_id).unwrap();
+    assert_eq!(c.get_internal_transfer_fee(), U256::from(25u64));
+
+    vm.set_sender(alice);
+    let payment_id = c.internal_transfer(bob, usdc, U256::from(4_000u64), "".into()).unwrap();
+    assert_eq!(c.get_user_balance(alice, usdc), U256::from(2_000u64));
+    assert_eq!(c.get_user_balance(bob, usdc), U256::from(7_990u64));
+    assert_eq!(c.get_accrued_fees(usdc), U256::from(10u64));
+    assert_eq!(c.get_claimable(treasury, usdc), U256::ZERO);
+    let (topics, data) = vm.get_emitted_logs().last().cloned().unwrap();
+    assert_eq!(topics[0], InternalTransferFeeCharged::SIGNATURE_HASH);
+    assert_eq!(U256::from_be_bytes(topics[1].0), payment_id);
+    assert_eq!(Address::from_word(topics[2]), usdc);
+    assert_eq!(InternalTransferFeeCharged::abi_decode_data(&data, true).unwrap(), (U256::from(10u64),));
+
+    // Only the current treasury collects the fees
+    let new_treasury = address!("0x3000000000000000000000000000000000000003");
+    vm.set_sender(owner);
+    let change_id = c.update_treasury(new_treasury).unwrap();
+    vm.set_block_timestamp(4 * 86400 + 2);
+    c.execute_governance_change(change_id).unwrap();
+    vm.set_sender(treasury);
+    let err = c.claim_fees(usdc).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::Unauthorized(_)));
+    vm.set_sender(new_treasury);
+    mock_transfer(&vm, usdc, new_treasury, U256::from(10u64));
+    c.claim_fees(usdc).unwrap();
+    assert_eq!(c.get_accrued_fees(usdc), U256::ZERO);
+    let err = c.claim_fees(usdc).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InsufficientBalance(_)));
+    vm.set_sender(alice);
+    let (_, _, _, _, total_sent, _, _) = c.get_user_profile(alice);
+    assert_eq!(total_sent, U256::from(8_000u64));
+    let (_, _, _, _, _, total_received, _) = c.get_user_profile(bob);
+    assert_eq!(total_received, U256::from(7_990u64));
+
+    // Only registered users can receive, and only what the sender holds can move
+    let err = c.internal_transfer(carol, usdc, U256::from(1u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::NotRegistered(_)));
+    let err = c.internal_transfer(bob, usdc, U256::from(2_001u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InsufficientBalance(_)));
+    let err = c.internal_transfer(alice, usdc, U256::from(1u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidRecipients(_)));
+}
+
+#[test]
+fn split_payment_pulls_once_and_groups_legs() {
+    use remittance_protocol::SplitPaymentSent;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let carol = address!("0xCA40100000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+    vm.set_sender(bob);
+    c.register_user("Bob".into(), "GH".into(), "000".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+    c.set_daily_limit(alice, U256::from(5_000u64)).unwrap();
+
+    // One pull of the total, one payout per recipient and one combined fee (5 + 15)
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(4_000u64));
+    mock_transfer(&vm, token, bob, U256::from(995u64));
+    mock_transfer(&vm, token, carol, U256::from(2_985u64));
+    mock_transfer(&vm, token, treasury, U256::from(20u64));
+
+    vm.set_sender(alice);
+    let recipients = vec![bob, carol];
+    let err = c.send_split_payment(recipients.clone(), vec![U256::from(1_000u64)], token, "rent".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidRecipients(_)));
+    let err = c.send_split_payment(recipients.clone(), vec![U256::from(1_000u64), U256::ZERO], token, "rent".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidAmount(_)));
+
+    let amounts = vec![U256::from(1_000u64), U256::from(3_000u64)];
+    let group_id = c.send_split_payment(recipients.clone(), amounts.clone(), token, "rent".into()).unwrap();
+    assert_eq!(group_id, U256::from(1u64));
+
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = SplitPaymentSent::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.totalAmount, U256::from(4_000u64));
+    assert_eq!(event.firstPaymentId, U256::ZERO);
+    assert_eq!(event.recipientCount, U256::from(2u64));
+
+    // Each leg is its own payment in the same group
+    for (payment_id, (recipient, amount)) in recipients.iter().zip(amounts.iter()).enumerate() {
+        let (sender, to, paid, tok, _, payment_type, note, completed) = c.get_payment(U256::from(payment_id)).unwrap();
+        assert_eq!((sender, to, paid, tok), (alice, *recipient, *amount, token));
+        assert_eq!(payment_type, U256::ZERO);
+        assert_eq!(note, "rent");
+        assert!(completed);
+        assert_eq!(c.get_payment_group(U256::from(payment_id)).unwrap(), group_id);
+    }
+
+    let (_, _, _, _, total_sent, _, _) = c.get_user_profile(alice);
+    assert_eq!(total_sent, U256::from(4_000u64));
+    let (_, _, _, _, _, total_received, _) = c.get_user_profile(bob);
+    assert_eq!(total_received, U256::from(995u64));
+
+    // The daily limit is checked against the whole split
+    let err = c.send_split_payment(recipients, vec![U256::from(500u64), U256::from(600u64)], token, "more".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::ExceedsLimit(_)));
+}
+
+#[test]
+fn send_payment_from_internal_balance() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+    vm.set_sender(bob);
+    c.register_user("Bob".into(), "US".into(), "000".into()).unwrap();
+
+    let token = address!("0xBBB0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+    c.set_daily_limit(alice, U256::from(15_000u64)).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(20_000u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(20_000u64)).unwrap();
+
+    // No transferFrom is mocked for the payment itself: only the payout legs
+    vm.set_block_timestamp(86_400);
+    mock_transfer(&vm, token, bob, U256::from(9_950u64));
+    mock_transfer(&vm, token, treasury, U256::from(50u64));
+    c.send_payment_from_balance(bob, U256::from(10_000u64), token, "Groceries".into()).unwrap();
+
+    assert_eq!(c.get_user_balance(alice, token), U256::from(10_000u64));
+    assert_eq!(c.get_daily_spent(alice), U256::from(10_000u64));
+    let (_, _, _, _, total_sent, _, _) = c.get_user_profile(alice);
+    assert_eq!(total_sent, U256::from(10_000u64));
+    let (_, _, _, _, _, total_received, _) = c.get_user_profile(bob);
+    assert_eq!(total_received, U256::from(9_950u64));
+    let (sender, recipient, amount, _, _, payment_type, note, completed) = c.get_payment(U256::ZERO).unwrap();
+    assert_eq!((sender, recipient, amount), (alice, bob, U256::from(10_000u64)));
+    assert_eq!(payment_type, U256::ZERO);
+    assert_eq!(note, "Groceries");
+    assert!(completed);
+
+    // The daily limit covers balance-funded payments too
+    let err = c.send_payment_from_balance(bob, U256::from(5_001u64), token, "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::ExceedsLimit(_)));
+
+    // And the balance must cover the whole amount
+    vm.set_block_timestamp(2 * 86_400);
+    let err = c.send_payment_from_balance(bob, U256::from(10_001u64), token, "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InsufficientBalance(_)));
+}
+
+#[test]
+fn scheduled_payment_execute_and_cancel() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let keeper = address!("0x4EE9000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xBBB0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(20_000u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(20_000u64)).unwrap();
+
+    // Schedule two payments; funds are reserved from the internal balance straight away
+    vm.set_block_timestamp(1_000);
+    let first = c.schedule_payment(bob, U256::from(10_000u64), token, U256::from(5_000u64), "School fees".into()).unwrap();
+    let second = c.schedule_payment(bob, U256::from(4_000u64), token, U256::from(5_000u64), "Rent".into()).unwrap();
+    assert_eq!(c.get_user_balance(alice, token), U256::from(6_000u64));
+
+    let (sender, recipient, amount, _, _, payment_type, note, completed) = c.get_payment(first).unwrap();
+    assert_eq!((sender, recipient, amount), (alice, bob, U256::from(10_000u64)));
+    assert_eq!(payment_type, U256::from(2u64));
+    assert_eq!(note, "School fees");
+    assert!(!completed);
+    assert_eq!(c.get_payment_schedule(first).unwrap(), (U256::from(5_000u64), false));
+
+    // Cannot reserve more than the remaining balance
+    let err = c.schedule_payment(bob, U256::from(6_001u64), token, U256::from(5_000u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InsufficientBalance(_)));
+
+    // Not executable before execute_after
+    vm.set_sender(keeper);
+    let err = c.execute_scheduled_payment(first).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::PaymentNotDue(_)));
+
+    // Only the sender may cancel, and cancelling refunds the reservation
+    let err = c.cancel_scheduled_payment(second).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::Unauthorized(_)));
+    vm.set_sender(alice);
+    c.cancel_scheduled_payment(second).unwrap();
+    assert_eq!(c.get_user_balance(alice, token), U256::from(10_000u64));
+    assert_eq!(c.get_payment_schedule(second).unwrap(), (U256::from(5_000u64), true));
+
+    // Anyone can execute once due: 9950 to bob, 50 fee to treasury
+    vm.set_block_timestamp(5_000);
+    mock_transfer(&vm, token, bob, U256::from(9_950u64));
+    mock_transfer(&vm, token, treasury, U256::from(50u64));
+    vm.set_sender(keeper);
+    c.execute_scheduled_payment(first).unwrap();
+    let (_, _, _, _, _, _, _, completed) = c.get_payment(first).unwrap();
+    assert!(completed);
+    let (_, _, _, _, total_sent, _, _) = c.get_user_profile(alice);
+    assert_eq!(total_sent, U256::from(10_000u64));
+
+    // Neither payment can run or be cancelled again
+    assert!(matches!(c.execute_scheduled_payment(first), Err(RemittanceErrors::PaymentNotPending(_))));
+    assert!(matches!(c.execute_scheduled_payment(second), Err(RemittanceErrors::PaymentNotPending(_))));
+    vm.set_sender(alice);
+    assert!(matches!(c.cancel_scheduled_payment(first), Err(RemittanceErrors::PaymentNotPending(_))));
+}
+
+#[test]
+fn cancelling_a_scheduled_payment_refunds_the_daily_limit_same_day() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xBBB0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+    c.set_daily_limit(alice, U256::from(5_000u64)).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(20_000u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(20_000u64)).unwrap();
+
+    // Scheduling uses up the day's allowance until it is cancelled
+    vm.set_block_timestamp(1_000);
+    let first = c.schedule_payment(bob, U256::from(5_000u64), token, U256::from(200_000u64), "".into()).unwrap();
+    let err = c.schedule_payment(bob, U256::from(1u64), token, U256::from(200_000u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::ExceedsLimit(_)));
+    c.cancel_scheduled_payment(first).unwrap();
+    let second = c.schedule_payment(bob, U256::from(5_000u64), token, U256::from(200_000u64), "".into()).unwrap();
+
+    // A cancellation on a later day leaves that day's allowance alone
+    vm.set_block_timestamp(86_400 + 1_000);
+    c.schedule_payment(bob, U256::from(5_000u64), token, U256::from(200_000u64), "".into()).unwrap();
+    c.cancel_scheduled_payment(second).unwrap();
+    let err = c.schedule_payment(bob, U256::from(1u64), token, U256::from(200_000u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::ExceedsLimit(_)));
+}
+
+#[test]
+fn beneficiary_add_update_remove_and_get_pending_estimate() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+    vm.set_sender(bob);
+    c.register_user("Bob".into(), "GH".into(), "000".into()).unwrap();
+
+    let token = address!("0xCCC0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    // Add beneficiary for alice
+    vm.set_sender(alice);
+    c.add_beneficiary(bob, "Bob".into(), "friend".into(), U256::from(200u64), token, U256::from(7u64)).unwrap();
+
+    let (addr, name, rel, amount, tok, freq, last_payment, active, total_sent) = c.get_beneficiary(alice, U256::ZERO).unwrap();
+    assert_eq!(addr, bob);
+    assert_eq!(name, "Bob");
+    assert_eq!(rel, "friend");
+    assert_eq!(amount, U256::from(200u64));
+    assert_eq!(tok, token);
+    assert_eq!(freq, U256::from(7u64));
+    assert_eq!(last_payment, U256::ZERO);
+    assert!(active);
+    assert_eq!(total_sent, U256::ZERO);
+
+    // Update beneficiary
+    vm.set_sender(alice);
+    c.update_beneficiary(U256::ZERO, U256::from(300u64), U256::from(30u64)).unwrap();
+    let (_, _, _, amount2, _, freq2, _, _, _) = c.get_beneficiary(alice, U256::ZERO).unwrap();
+    assert_eq!(amount2, U256::from(300u64));
+    assert_eq!(freq2, U256::from(30u64));
+
+    // get_pending_auto_payments -> none because no balance
+    let pending = c.get_pending_auto_payments(alice);
+    assert!(pending.is_empty());
+
+    // Seed balance so pending appears
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+
+    // Now pending should contain index 0 (because last_payment==0 and user has balance >= amount)
+    let pending2 = c.get_pending_auto_payments(alice);
+    assert_eq!(pending2.len(), 1);
+    assert_eq!(pending2[0], U256::from(0u64));
+
+    // estimate next payment time -> since last_payment == 0 should return current block timestamp (can execute now)
+    let est = c.estimate_next_payment_time(alice, U256::ZERO).unwrap();
+    assert!(est > U256::ZERO || est == U256::from(vm.block_timestamp())); // implementation returns block_timestamp if last_payment==0
+
+    // Remove beneficiary and ensure subsequent get fails
+    vm.set_sender(alice);
+    c.remove_beneficiary(U256::ZERO).unwrap();
+    let err = c.get_beneficiary(alice, U256::ZERO).unwrap_err();
+    match err {
+        RemittanceErrors::BeneficiaryNotFound(_) => {}
+        _ => panic!("expected BeneficiaryNotFound"),
+    }
+}
+
+#[test]
+fn execute_auto_payment_and_frequency_lock() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+    vm.set_sender(bob);
+    c.register_user("Bob".into(), "GH".into(), "000".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    // add beneficiary with daily frequency (1)
+    vm.set_sender(alice);
+    c.add_beneficiary(bob, "Bob".into(), "friend".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
+
+    // seed alice internal balance by deposit path
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+
+    // 50 at 0.5% has no fee, so the whole amount goes to bob
+    mock_transfer(&vm, token, bob, U256::from(50u64));
+
+    // set block timestamp to known value
+    vm.set_block_timestamp(1000);
+    // execute
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+
+    // beneficiary last_payment updated to 1000 and total_sent increased
+    let (_, _, _, _, _, _, last_payment, _, total_sent) = c.get_beneficiary(alice, U256::ZERO).unwrap();
+    assert_eq!(last_payment, U256::from(1000u64));
+    assert_eq!(total_sent, U256::from(50u64));
+
+    // trying to execute again immediately should fail with FrequencyNotMet
+    let err = c.execute_auto_payments(alice, U256::ZERO).unwrap_err();
+    match err {
+        RemittanceErrors::FrequencyNotMet(_) => {}
+        _ => panic!("expected FrequencyNotMet"),
+    }
+
+    // advance time by 2 days (2*86400)
+    vm.set_block_timestamp(1000 + 2 * 86400);
+    // Now should succeed again
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+}
+
+#[test]
+fn monthly_and_yearly_schedules_follow_the_calendar() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer(&vm, token, bob, U256::from(50u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+    const JAN_10_2025: u64 = 1_736_467_200;
+    const JAN_25_2025: u64 = 1_737_763_200;
+    const JAN_31_2025: u64 = 1_738_281_600;
+    const FEB_25_2025: u64 = 1_740_441_600;
+    const FEB_28_2025: u64 = 1_740_700_800;
+    const MAR_31_2025: u64 = 1_743_379_200;
+    const FEB_29_2024: u64 = 1_709_164_800;
+    const FEB_28_2026: u64 = 1_772_236_800;
+
+    // 0: monthly, anchored by its first payment on Jan 31
+    // 1: monthly, pinned to the 25th
+    // 2: yearly, pinned to Feb 29
+    // 3: weekly, cannot take an anchor
+    vm.set_block_timestamp(JAN_1_2024);
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(30u64)).unwrap();
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(30u64)).unwrap();
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(365u64)).unwrap();
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(7u64)).unwrap();
+    c.set_payment_anchor(U256::from(2u64), 29, 2).unwrap();
+    assert_eq!(c.get_payment_anchor(alice, U256::from(2u64)).unwrap(), (29, 2));
+    assert!(matches!(c.set_payment_anchor(U256::from(3u64), 1, 0), Err(RemittanceErrors::InvalidFrequency(_))));
+    assert!(matches!(c.set_payment_anchor(U256::from(2u64), 30, 2), Err(RemittanceErrors::InvalidConfiguration(_))));
+    assert!(matches!(c.set_payment_anchor(U256::from(1u64), 32, 0), Err(RemittanceErrors::InvalidConfiguration(_))));
+
+    // Jan 31 clamps to Feb 28, then returns to the 31st in March
+    vm.set_block_timestamp(JAN_31_2025 + 10 * 3600);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    assert_eq!(c.get_payment_anchor(alice, U256::ZERO).unwrap(), (31, 0));
+    assert_eq!(c.estimate_next_payment_time(alice, U256::ZERO).unwrap(), U256::from(FEB_28_2025));
+    vm.set_block_timestamp(FEB_28_2025 - 1);
+    assert!(matches!(c.execute_auto_payments(alice, U256::ZERO), Err(RemittanceErrors::FrequencyNotMet(_))));
+    vm.set_block_timestamp(FEB_28_2025 + 5 * 3600);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    assert_eq!(c.estimate_next_payment_time(alice, U256::ZERO).unwrap(), U256::from(MAR_31_2025));
+
+    // Pinned to the 25th on the 10th, the first payment waits for the 25th
+    vm.set_block_timestamp(JAN_10_2025);
+    c.set_payment_anchor(U256::from(1u64), 25, 0).unwrap();
+    assert!(matches!(c.execute_auto_payments(alice, U256::from(1u64)), Err(RemittanceErrors::FrequencyNotMet(_))));
+    assert_eq!(c.estimate_next_payment_time(alice, U256::from(1u64)).unwrap(), U256::from(JAN_25_2025));
+    // Paying late does not move the anchor
+    vm.set_block_timestamp(JAN_25_2025 + 3 * 86400);
+    c.execute_auto_payments(alice, U256::from(1u64)).unwrap();
+    assert_eq!(c.estimate_next_payment_time(alice, U256::from(1u64)).unwrap(), U256::from(FEB_25_2025));
+    assert!(!c.get_pending_auto_payments(alice).contains(&U256::from(1u64)));
+
+    // Feb 29 lands on Feb 28 outside leap years
+    vm.set_block_timestamp(FEB_29_2024);
+    c.execute_auto_payments(alice, U256::from(2u64)).unwrap();
+    assert_eq!(c.estimate_next_payment_time(alice, U256::from(2u64)).unwrap(), U256::from(FEB_28_2025));
+    vm.set_block_timestamp(FEB_28_2025);
+    c.execute_auto_payments(alice, U256::from(2u64)).unwrap();
+    assert_eq!(c.estimate_next_payment_time(alice, U256::from(2u64)).unwrap(), U256::from(FEB_28_2026));
+}
+
+#[test]
+fn custom_interval_schedules_respect_start_end_and_cap() {
+    use remittance_protocol::PaymentScheduleCompleted;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer(&vm, token, bob, U256::from(50u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+
+    // Any whole number of days inside the bounds is accepted
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(14u64)).unwrap();
+    let err = c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(400u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidFrequency(_)));
+
+    // Only limit managers move the bounds
+    assert!(matches!(c.set_payment_interval_bounds(U256::from(600u64), U256::from(30 * 86400u64)), Err(RemittanceErrors::Unauthorized(_))));
+    vm.set_sender(owner);
+    let err = c.set_payment_interval_bounds(U256::from(600u64), U256::from(10 * 365 * 86400u64 + 1)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+    let err = c.set_payment_interval_bounds(U256::from(600u64), U256::MAX).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+    c.set_payment_interval_bounds(U256::from(600u64), U256::from(30 * 86400u64)).unwrap();
+    assert_eq!(c.get_payment_interval_bounds(), (U256::from(600u64), U256::from(30 * 86400u64)));
+    vm.set_sender(alice);
+    let err = c.update_beneficiary(U256::ZERO, U256::from(50u64), U256::from(365u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidFrequency(_)));
+    let err = c.set_payment_schedule(U256::ZERO, U256::from(300u64), U256::ZERO, U256::ZERO, U256::ZERO).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidFrequency(_)));
+
+    // Every 2 hours from t=10_000, three times at most
+    vm.set_block_timestamp(5_000);
+    c.set_payment_schedule(U256::ZERO, U256::from(7_200u64), U256::from(10_000u64), U256::ZERO, U256::from(3u64)).unwrap();
+    assert!(c.get_pending_auto_payments(alice).is_empty());
+    assert_eq!(c.estimate_next_payment_time(alice, U256::ZERO).unwrap(), U256::from(10_000u64));
+    assert!(matches!(c.execute_auto_payments(alice, U256::ZERO), Err(RemittanceErrors::FrequencyNotMet(_))));
+
+    vm.set_block_timestamp(10_000);
+    assert_eq!(c.get_pending_auto_payments(alice), vec![U256::ZERO]);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    vm.set_block_timestamp(10_000 + 7_199);
+    assert!(matches!(c.execute_auto_payments(alice, U256::ZERO), Err(RemittanceErrors::FrequencyNotMet(_))));
+    vm.set_block_timestamp(10_000 + 7_200);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    vm.set_block_timestamp(10_000 + 2 * 7_200);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+
+    // The third run retires the beneficiary, which stays readable
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = PaymentScheduleCompleted::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.executions, U256::from(3u64));
+    let (_, _, _, _, _, _, _, active, total_sent) = c.get_beneficiary(alice, U256::ZERO).unwrap();
+    assert!(!active);
+    assert_eq!(total_sent, U256::from(150u64));
+    assert_eq!(c.get_beneficiary_schedule(alice, U256::ZERO).unwrap().4, U256::from(3u64));
+    vm.set_block_timestamp(10_000 + 3 * 7_200);
+    assert!(c.execute_auto_payments(alice, U256::ZERO).is_err());
+    assert_eq!(c.estimate_next_payment_time(alice, U256::ZERO).unwrap(), U256::ZERO);
+
+    // Past its end date a schedule stops running and reads as inactive
+    let now = 10_000 + 3 * 7_200;
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
+    let err = c.set_payment_schedule(U256::from(1u64), U256::from(3_600u64), U256::ZERO, U256::from(now), U256::ZERO).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+    c.set_payment_schedule(U256::from(1u64), U256::from(3_600u64), U256::ZERO, U256::from(now + 5_000), U256::ZERO).unwrap();
+    c.execute_auto_payments(alice, U256::from(1u64)).unwrap();
+    assert_eq!(c.estimate_next_payment_time(alice, U256::from(1u64)).unwrap(), U256::from(now + 3_600));
+    vm.set_block_timestamp(now + 5_001);
+    assert!(matches!(c.execute_auto_payments(alice, U256::from(1u64)), Err(RemittanceErrors::ScheduleEnded(_))));
+    assert!(!c.get_pending_auto_payments(alice).contains(&U256::from(1u64)));
+    let (_, _, _, _, _, _, _, active, _) = c.get_beneficiary(alice, U256::from(1u64)).unwrap();
+    assert!(!active);
+}
+
+#[test]
+fn keeper_is_tipped_from_user_balance() {
+    use remittance_protocol::AutoPaymentExecuted;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let keeper = address!("0x4EE9E00000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer(&vm, token, bob, U256::from(50u64));
+    mock_transfer(&vm, token, keeper, U256::from(5u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
+
+    let unlisted = address!("0xEEE0000000000000000000000000000000000000");
+    assert!(matches!(c.set_keeper_tip(unlisted, U256::from(5u64)), Err(RemittanceErrors::NotSupportedToken(_))));
+    c.set_keeper_tip(token, U256::from(5u64)).unwrap();
+    assert_eq!(c.get_keeper_tip(alice, token), U256::from(5u64));
+
+    // A third party running the payment earns the tip
+    vm.set_block_timestamp(1_000);
+    vm.set_sender(keeper);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    assert_eq!(c.get_user_balance(alice, token), U256::from(945u64));
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = AutoPaymentExecuted::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.keeper, keeper);
+    assert_eq!(event.keeperTip, U256::from(5u64));
+
+    // Alice running her own payment pays no tip
+    vm.set_block_timestamp(1_000 + 86400);
+    vm.set_sender(alice);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    assert_eq!(c.get_user_balance(alice, token), U256::from(895u64));
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = AutoPaymentExecuted::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.keeperTip, U256::ZERO);
+
+    // The tip never exceeds what is left after the payment
+    c.set_keeper_tip(token, U256::from(10_000u64)).unwrap();
+    mock_transfer(&vm, token, keeper, U256::from(845u64));
+    vm.set_block_timestamp(1_000 + 2 * 86400);
+    vm.set_sender(keeper);
+    c.batch_execute_auto_payments(vec![(alice, U256::ZERO)]).unwrap();
+    assert_eq!(c.get_user_balance(alice, token), U256::ZERO);
+    mock_balance_of(&vm, token, contract_addr, U256::ZERO);
+    let (_, liabilities, _, _) = c.get_token_solvency(token).unwrap();
+    assert_eq!(liabilities, U256::ZERO);
+}
+
+#[test]
+fn catch_up_policy_pays_missed_periods_and_keeps_phase() {
+    use remittance_protocol::{AutoPaymentExecuted, CATCH_UP_ALL, CATCH_UP_MAX_N, CATCH_UP_SKIP};
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    // One period of 100 carries no fee; two or three periods carry a fee of 1
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    for payout in [100u64, 199, 299] {
+        mock_transfer(&vm, token, bob, U256::from(payout));
+    }
+    mock_transfer(&vm, token, treasury, U256::from(1u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+
+    for _ in 0..3 {
+        c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(100u64), token, U256::from(1u64)).unwrap();
+    }
+    assert!(matches!(c.set_catch_up_policy(U256::ZERO, 3, U256::ZERO), Err(RemittanceErrors::InvalidConfiguration(_))));
+    assert!(matches!(c.set_catch_up_policy(U256::ZERO, CATCH_UP_MAX_N, U256::ZERO), Err(RemittanceErrors::InvalidConfiguration(_))));
+    c.set_catch_up_policy(U256::from(1u64), CATCH_UP_ALL, U256::ZERO).unwrap();
+    c.set_catch_up_policy(U256::from(2u64), CATCH_UP_MAX_N, U256::from(2u64)).unwrap();
+    assert_eq!(c.get_catch_up_policy(alice, U256::ZERO).unwrap(), (CATCH_UP_SKIP, U256::ZERO));
+    assert_eq!(c.get_catch_up_policy(alice, U256::from(2u64)).unwrap(), (CATCH_UP_MAX_N, U256::from(2u64)));
+
+    // The first payments set the phase at t0
+    let t0 = 1_000u64;
+    vm.set_block_timestamp(t0);
+    for i in 0..3u64 {
+        c.execute_auto_payments(alice, U256::from(i)).unwrap();
+    }
+    assert_eq!(c.get_user_balance(alice, token), U256::from(700u64));
+
+    // Three and a half days later, three periods are due
+    let now = t0 + 3 * 86400 + 43_200;
+    vm.set_block_timestamp(now);
+    c.execute_auto_payments(alice, U256::ZERO).unwrap();
+    c.execute_auto_payments(alice, U256::from(1u64)).unwrap();
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = AutoPaymentExecuted::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.periods, U256::from(3u64));
+    assert_eq!(event.amount, U256::from(300u64));
+    c.execute_auto_payments(alice, U256::from(2u64)).unwrap();
+    assert_eq!(c.get_user_balance(alice, token), U256::from(100u64));
+
+    // Every policy lands on the latest period boundary, not on the execution time
+    for i in 0..3u64 {
+        let (_, _, _, _, _, _, last_payment, _, _) = c.get_beneficiary(alice, U256::from(i)).unwrap();
+        assert_eq!(last_payment, U256::from(t0 + 3 * 86400));
+        assert_eq!(c.estimate_next_payment_time(alice, U256::from(i)).unwrap(), U256::from(t0 + 4 * 86400));
+    }
+    let (_, _, _, _, _, _, _, _, total_sent) = c.get_beneficiary(alice, U256::from(2u64)).unwrap();
+    assert_eq!(total_sent, U256::from(300u64));
+
+    // Catch-up stops at what the balance covers; the rest stays due
+    let later = t0 + 8 * 86400 + 43_200;
+    vm.set_block_timestamp(later);
+    c.execute_auto_payments(alice, U256::from(1u64)).unwrap();
+    let (_, _, _, _, _, _, last_payment, _, _) = c.get_beneficiary(alice, U256::from(1u64)).unwrap();
+    assert_eq!(last_payment, U256::from(t0 + 4 * 86400));
+    assert_eq!(c.estimate_next_payment_time(alice, U256::from(1u64)).unwrap(), U256::from(later));
+    let err = c.execute_auto_payments(alice, U256::from(1u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InsufficientBalance(_)));
+}
+
+#[test]
+fn due_queue_orders_and_executes_schedules() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let carol = address!("0xCA40100000000000000000000000000000000000");
+    for (user, name) in [(alice, "Alice"), (carol, "Carol")] {
+        vm.set_sender(user);
+        c.register_user(name.into(), "NG".into(), "0803".into()).unwrap();
+    }
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer(&vm, token, bob, U256::from(50u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+
+    // Alice: staggered starts, queued out of order; a manual beneficiary stays out
+    vm.set_block_timestamp(1_000);
+    let starts = [40_000u64, 10_000, 30_000, 20_000, 50_000];
+    for (i, start) in starts.iter().enumerate() {
+        c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
+        c.set_payment_schedule(U256::from(i), U256::ZERO, U256::from(*start), U256::ZERO, U256::ZERO).unwrap();
+    }
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::ZERO).unwrap();
+    assert_eq!(c.get_due_queue_length(), U256::from(5u64));
+
+    // Carol cannot cover her payment
+    vm.set_sender(carol);
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
+    c.set_payment_schedule(U256::ZERO, U256::ZERO, U256::from(25_000u64), U256::ZERO, U256::ZERO).unwrap();
+    assert_eq!(c.get_due_queue_length(), U256::from(6u64));
+
+    // Only entries already due are listed, earliest first
+    assert!(c.peek_due(U256::from(10u64)).is_empty());
+    vm.set_block_timestamp(35_000);
+    let due = c.peek_due(U256::from(10u64));
+    assert_eq!(
+        due,
+        vec![
+            (alice, U256::from(1u64), U256::from(10_000u64)),
+            (alice, U256::from(3u64), U256::from(20_000u64)),
+            (carol, U256::ZERO, U256::from(25_000u64)),
+            (alice, U256::from(2u64), U256::from(30_000u64)),
+        ]
+    );
+    assert_eq!(c.peek_due(U256::from(2u64)).len(), 2);
+
+    // Anyone can drain the queue in bounded steps; carol's item is deferred, not blocking
+    let keeper = address!("0x4EE9E00000000000000000000000000000000000");
+    vm.set_sender(keeper);
+    assert_eq!(c.execute_due(U256::from(2u64)).unwrap(), U256::from(2u64));
+    assert_eq!(c.execute_due(U256::from(10u64)).unwrap(), U256::from(1u64));
+    assert!(c.peek_due(U256::from(10u64)).is_empty());
+    assert_eq!(c.get_user_balance(alice, token), U256::from(850u64));
+
+    // Executed schedules are re-queued at their next due time
+    vm.set_block_timestamp(35_000 + 3_600);
+    let due = c.peek_due(U256::from(10u64));
+    assert_eq!(due, vec![(carol, U256::ZERO, U256::from(35_000u64 + 3_600))]);
+    vm.set_block_timestamp(35_000 + 86400);
+    let due = c.peek_due(U256::from(10u64));
+    assert_eq!(due.len(), 6);
+    assert_eq!(due[1], (alice, U256::ZERO, U256::from(40_000u64)));
+    assert_eq!(due[2], (alice, U256::from(4u64), U256::from(50_000u64)));
+    assert!(due.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+
+    // Removing a beneficiary or switching it to manual takes it out of the queue
+    vm.set_sender(alice);
+    c.remove_beneficiary(U256::from(4u64)).unwrap();
+    c.update_beneficiary(U256::from(0u64), U256::from(50u64), U256::ZERO).unwrap();
+    assert_eq!(c.get_due_queue_length(), U256::from(4u64));
+    assert!(c.peek_due(U256::from(10u64)).iter().all(|entry| entry.1 != U256::ZERO || entry.0 == carol));
+    c.update_beneficiary(U256::from(5u64), U256::from(50u64), U256::from(7u64)).unwrap();
+    assert_eq!(c.get_due_queue_length(), U256::from(5u64));
+}
+
+#[test]
+fn batch_execute_auto_payments_returns_results() {
+    use remittance_protocol::{BatchExecuted, BeneficiaryNotFound, FrequencyNotMet, InsufficientBalance};
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    // create two users and their beneficiaries
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let charlie = address!("0xC0C0000000000000000000000000000000000000");
 
     vm.set_sender(alice);
     c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
@@ -556,7 +1960,7 @@ fn batch_execute_auto_payments_returns_results() {
     vm.set_sender(charlie);
     c.register_user("Charlie".into(), "KE".into(), "000".into()).unwrap();
 
-    let token = put_token(MockERC20::deployed_at(address!("0xEEE0000000000000000000000000000000000000")));
+    let token = address!("0xEEE0000000000000000000000000000000000000");
     vm.set_sender(owner);
     c.add_supported_token(token).unwrap();
 
@@ -568,8 +1972,10 @@ fn batch_execute_auto_payments_returns_results() {
 
     // seed balances
     let contract_addr = c.vm().contract_address();
-    seed_token_balance_and_approve(token, alice, contract_addr, U256::from(100u64));
-    seed_token_balance_and_approve(token, bob, contract_addr, U256::from(100u64));
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(100u64));
+    mock_transfer_from(&vm, token, bob, contract_addr, U256::from(100u64));
+    mock_transfer(&vm, token, bob, U256::from(10u64));
+    mock_transfer(&vm, token, charlie, U256::from(20u64));
 
     vm.set_sender(alice);
     c.deposit_balance(token, U256::from(100u64)).unwrap();
@@ -577,23 +1983,248 @@ fn batch_execute_auto_payments_returns_results() {
     c.deposit_balance(token, U256::from(100u64)).unwrap();
 
     // batch execute two entries: (alice, 0), (bob, 0)
+    vm.set_block_timestamp(1_000);
     vm.set_sender(owner); // caller of batch execution can be owner or anyone; implementation only when_not_paused
-    let res = c.batch_execute_auto_payments(vec![(alice, U256::ZERO), (bob, U256::ZERO)]).unwrap();
-    // Both should succeed (true/true)
-    assert_eq!(res.len(), 2);
-    assert!(res[0]);
-    assert!(res[1]);
+    let (_, res) = c.batch_execute_auto_payments(vec![(alice, U256::ZERO), (bob, U256::ZERO)]).unwrap();
+    // Both should succeed, with consecutive execution ids
+    assert_eq!(res, vec![(BATCH_STATUS_OK, U256::ZERO), (BATCH_STATUS_OK, U256::from(1u64))]);
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = BatchExecuted::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!((event.keeper, event.succeeded, event.failed), (owner, U256::from(2u64), U256::ZERO));
+
+    // Failed items report the error that stopped them
+    vm.set_sender(charlie);
+    c.add_beneficiary(alice, "Alice".into(), "friend".into(), U256::from(1_000u64), token, U256::from(1u64)).unwrap();
+    vm.set_sender(owner);
+    let (_, res) = c
+        .batch_execute_auto_payments(vec![(alice, U256::ZERO), (charlie, U256::ZERO), (charlie, U256::from(1u64))])
+        .unwrap();
+    let frequency_not_met = RemittanceErrors::FrequencyNotMet(FrequencyNotMet {}).code();
+    let insufficient_balance = RemittanceErrors::InsufficientBalance(InsufficientBalance {}).code();
+    let not_found = RemittanceErrors::BeneficiaryNotFound(BeneficiaryNotFound {}).code();
+    assert_eq!((frequency_not_met, insufficient_balance, not_found), (11, 4, 13));
+    assert_eq!(
+        res,
+        vec![(frequency_not_met, U256::ZERO), (insufficient_balance, U256::ZERO), (not_found, U256::ZERO)]
+    );
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = BatchExecuted::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!((event.succeeded, event.failed), (U256::ZERO, U256::from(3u64)));
+}
+
+#[test]
+fn batch_stops_before_running_out_of_gas() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xEEE0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(100u64));
+    mock_transfer(&vm, token, bob, U256::from(10u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(100u64)).unwrap();
+    for _ in 0..3 {
+        c.add_beneficiary(bob, "Bob".into(), "friend".into(), U256::from(10u64), token, U256::from(1u64)).unwrap();
+    }
+    let items = vec![(alice, U256::ZERO), (alice, U256::from(1u64)), (alice, U256::from(2u64))];
+
+    // Too little gas for even one item: nothing runs and nothing reverts
+    vm.set_block_timestamp(1_000);
+    vm.set_gas_left(100_000);
+    let (processed, res) = c.batch_execute_auto_payments(items.clone()).unwrap();
+    assert_eq!(processed, U256::ZERO);
+    assert!(res.is_empty());
+    assert_eq!(c.execute_due(U256::from(10u64)).unwrap(), U256::ZERO);
+    assert_eq!(c.get_user_balance(alice, token), U256::from(100u64));
+
+    // The keeper resubmits from the cursor once gas allows
+    vm.set_gas_left(u64::MAX);
+    let (processed, res) = c.batch_execute_auto_payments(items[processed.to::<usize>()..].to_vec()).unwrap();
+    assert_eq!(processed, U256::from(3u64));
+    assert!(res.iter().all(|(status, _)| *status == BATCH_STATUS_OK));
+    assert_eq!(c.get_user_balance(alice, token), U256::from(70u64));
+}
+
+#[test]
+fn batch_rolls_back_item_when_token_transfer_fails() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let charlie = address!("0xC0C0000000000000000000000000000000000000");
+    let dave = address!("0xDA7E000000000000000000000000000000000000");
+    let erin = address!("0xE121000000000000000000000000000000000000");
+
+    for user in [alice, bob, charlie] {
+        vm.set_sender(user);
+        c.register_user("User".into(), "NG".into(), "000".into()).unwrap();
+    }
+
+    let token = address!("0xEEE0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    // alice and charlie send fee-free amounts to dave; bob's 10_000 to erin carries
+    // a 50 fee, and the token rejects that treasury transfer
+    vm.set_sender(alice);
+    c.add_beneficiary(dave, "Dave".into(), "family".into(), U256::from(10u64), token, U256::from(1u64)).unwrap();
+    vm.set_sender(bob);
+    c.add_beneficiary(erin, "Erin".into(), "family".into(), U256::from(10_000u64), token, U256::from(1u64)).unwrap();
+    vm.set_sender(charlie);
+    c.add_beneficiary(dave, "Dave".into(), "family".into(), U256::from(30u64), token, U256::from(1u64)).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    for user in [alice, bob, charlie] {
+        mock_transfer_from(&vm, token, user, contract_addr, U256::from(20_000u64));
+        vm.set_sender(user);
+        c.deposit_balance(token, U256::from(20_000u64)).unwrap();
+    }
+
+    mock_transfer(&vm, token, dave, U256::from(10u64));
+    mock_transfer(&vm, token, erin, U256::from(9_950u64));
+    mock_transfer_revert(&vm, token, treasury, U256::from(50u64));
+    mock_transfer(&vm, token, dave, U256::from(30u64));
+
+    let transfer_failed = RemittanceErrors::TransferFailed(remittance_protocol::TransferFailed {}).code();
+    vm.set_block_timestamp(1_000);
+    vm.set_sender(owner);
+    let (_, res) = c
+        .batch_execute_auto_payments(vec![(alice, U256::ZERO), (bob, U256::ZERO), (charlie, U256::ZERO)])
+        .unwrap();
+    assert_eq!(res, vec![(BATCH_STATUS_OK, U256::ZERO), (transfer_failed, U256::ZERO), (BATCH_STATUS_OK, U256::from(1u64))]);
+
+    // The failed item left no trace: balance, schedule and stats are untouched
+    assert_eq!(c.get_user_balance(bob, token), U256::from(20_000u64));
+    let (_, _, _, _, _, _, last_payment, active, total_sent) = c.get_beneficiary(bob, U256::ZERO).unwrap();
+    assert_eq!(last_payment, U256::ZERO);
+    assert!(active);
+    assert_eq!(total_sent, U256::ZERO);
+    let (_, _, _, _, bob_sent, _, _) = c.get_user_profile(bob);
+    assert_eq!(bob_sent, U256::ZERO);
+    assert_eq!(c.get_claimable(erin, token), U256::ZERO);
+
+    // Items on either side of it went through
+    assert_eq!(c.get_user_balance(alice, token), U256::from(19_990u64));
+    assert_eq!(c.get_user_balance(charlie, token), U256::from(19_970u64));
+    let (_, exec_count, _, _, _) = c.get_contract_stats();
+    assert_eq!(exec_count, U256::from(2u64));
+
+    // A token answering `false` instead of reverting is rolled back the same way
+    vm.mock_call(token, encode_transfer(treasury, U256::from(50u64)), Ok(vec![0; 32]));
+    let (_, res) = c.batch_execute_auto_payments(vec![(bob, U256::ZERO)]).unwrap();
+    assert_eq!(res, vec![(transfer_failed, U256::ZERO)]);
+    assert_eq!(c.get_user_balance(bob, token), U256::from(20_000u64));
+
+    // Once the token accepts the transfer, bob's schedule runs normally
+    mock_transfer(&vm, token, treasury, U256::from(50u64));
+    let (_, res) = c.batch_execute_auto_payments(vec![(bob, U256::ZERO)]).unwrap();
+    assert_eq!(res, vec![(BATCH_STATUS_OK, U256::from(2u64))]);
+    assert_eq!(c.get_user_balance(bob, token), U256::from(10_000u64));
+}
+
+#[test]
+fn refused_payout_is_parked_and_claimable() {
+    use remittance_protocol::{PaymentClaimed, PaymentParked};
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xEEE0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    vm.set_sender(alice);
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(10_000u64), token, U256::from(1u64)).unwrap();
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(10_000u64));
+    c.deposit_balance(token, U256::from(10_000u64)).unwrap();
+
+    // bob is blacklisted by the token: the fee still goes out, the payout is parked
+    mock_transfer(&vm, token, treasury, U256::from(50u64));
+    mock_transfer_revert(&vm, token, bob, U256::from(9_950u64));
+
+    vm.set_block_timestamp(1_000);
+    vm.set_sender(owner);
+    let (_, res) = c.batch_execute_auto_payments(vec![(alice, U256::ZERO)]).unwrap();
+    assert_eq!(res, vec![(BATCH_STATUS_OK, U256::ZERO)]);
+
+    assert_eq!(c.get_user_balance(alice, token), U256::ZERO);
+    assert_eq!(c.get_claimable(bob, token), U256::from(9_950u64));
+    let (_, _, _, _, _, _, last_payment, _, total_sent) = c.get_beneficiary(alice, U256::ZERO).unwrap();
+    assert_eq!(last_payment, U256::from(1_000u64));
+    assert_eq!(total_sent, U256::from(10_000u64));
+
+    let parked = vm
+        .get_emitted_logs()
+        .into_iter()
+        .find_map(|(topics, data)| PaymentParked::decode_raw_log(topics, &data, true).ok())
+        .unwrap();
+    assert_eq!((parked.recipient, parked.token, parked.sender), (bob, token, alice));
+    assert_eq!(parked.amount, U256::from(9_950u64));
+
+    // Parked funds still count as owed to users
+    mock_balance_of(&vm, token, contract_addr, U256::from(9_950u64));
+    let (_, liabilities, surplus, _) = c.get_token_solvency(token).unwrap();
+    assert_eq!(liabilities, U256::from(9_950u64));
+    assert_eq!(surplus, U256::ZERO);
+
+    // Once unblocked, bob pulls the funds
+    mock_transfer(&vm, token, bob, U256::from(9_950u64));
+    vm.set_sender(bob);
+    c.claim(token).unwrap();
+    assert_eq!(c.get_claimable(bob, token), U256::ZERO);
+
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let claimed = PaymentClaimed::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!((claimed.recipient, claimed.token, claimed.amount), (bob, token, U256::from(9_950u64)));
+
+    // Nothing left to claim
+    let err = c.claim(token).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InsufficientBalance(_)));
 }
 
 #[test]
 fn admin_only_and_pause_emergency_withdraw() {
+    use remittance_protocol::{EmergencyWithdrawal, TREASURY_ADMIN_ROLE};
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
     let vm = TestVM::default();
     let mut c = UniversalRemittance::from(&vm);
 
     let owner = address!("0x1000000000000000000000000000000000000001");
     let treasury = address!("0x2000000000000000000000000000000000000002");
     vm.set_sender(owner);
-    c.constructor(treasury).unwrap();
+    c.constructor(owner, treasury).unwrap();
 
     let not_owner = address!("0xDEAD000000000000000000000000000000000000");
     vm.set_sender(not_owner);
@@ -621,14 +2252,10 @@ fn admin_only_and_pause_emergency_withdraw() {
         _ => panic!("expected InvalidConfiguration"),
     }
 
-    // emergency withdraw: put some tokens in contract, ensure owner can withdraw
-    let token = put_token(MockERC20::deployed_at(address!("0xFFF0000000000000000000000000000000000000")));
-    // seed contract token balance directly
-    TOKENS.with(|m| {
-        let mut map = m.borrow_mut();
-        let t = map.get_mut(&token).unwrap();
-        t.mint(c.vm().contract_address(), U256::from(1_000u64));
-    });
+    // emergency withdraw: put some tokens in contract, ensure owner can withdraw to the treasury
+    let token = address!("0xFFF0000000000000000000000000000000000000");
+    mock_balance_of(&vm, token, c.vm().contract_address(), U256::from(1_000u64));
+    mock_transfer(&vm, token, treasury, U256::from(100u64));
 
     // not owner cannot emergency_withdraw
     vm.set_sender(not_owner);
@@ -641,12 +2268,334 @@ fn admin_only_and_pause_emergency_withdraw() {
     vm.set_sender(owner);
     c.emergency_withdraw(token, U256::from(100u64)).unwrap();
 
-    // verify owner balance increased
-    TOKENS.with(|m| {
-        let map = m.borrow();
-        let t = map.get(&token).unwrap();
-        assert_eq!(t.balance_of(owner), U256::from(100u64));
-    });
+    // Renouncing ownership leaves the treasury admin paying out to the treasury, not address zero
+    let treasury_admin = address!("0x7AD0000000000000000000000000000000000000");
+    c.grant_role(TREASURY_ADMIN_ROLE, treasury_admin).unwrap();
+    c.renounce_ownership().unwrap();
+    assert_eq!(c.owner(), Address::ZERO);
+    vm.set_sender(treasury_admin);
+    c.emergency_withdraw(token, U256::from(100u64)).unwrap();
+    c.queue_emergency_withdraw(token, U256::from(100u64)).unwrap();
+    vm.set_block_timestamp(2 * 86400);
+    c.execute_emergency_withdraw(token).unwrap();
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = EmergencyWithdrawal::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.to, treasury);
+}
+
+#[test]
+fn emergency_withdraw_limited_to_surplus_and_timelocked_drain() {
+    use remittance_protocol::EmergencyWithdrawal;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xFFF0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    // alice deposits 1000 and withdraws 100; the contract also holds 200 of stray tokens
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer(&vm, token, alice, U256::from(100u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+    c.withdraw_balance(token, U256::from(100u64)).unwrap();
+    mock_balance_of(&vm, token, contract_addr, U256::from(1_100u64));
+
+    let (balance, liabilities, surplus, shortfall) = c.get_token_solvency(token).unwrap();
+    assert_eq!(balance, U256::from(1_100u64));
+    assert_eq!(liabilities, U256::from(900u64));
+    assert_eq!(surplus, U256::from(200u64));
+    assert_eq!(shortfall, U256::ZERO);
+
+    // The immediate path cannot touch user funds
+    vm.set_sender(owner);
+    let err = c.emergency_withdraw(token, U256::from(201u64)).unwrap_err();
+    match err {
+        RemittanceErrors::ExceedsSurplus(_) => {}
+        _ => panic!("expected ExceedsSurplus, got {:?}", err),
+    }
+    mock_transfer(&vm, token, treasury, U256::from(200u64));
+    c.emergency_withdraw(token, U256::from(200u64)).unwrap();
+
+    // The full drain has to wait out the timelock
+    vm.set_block_timestamp(10_000);
+    mock_balance_of(&vm, token, contract_addr, U256::from(900u64));
+    c.queue_emergency_withdraw(token, U256::from(900u64)).unwrap();
+    assert_eq!(
+        c.get_pending_emergency_withdraw(token),
+        (U256::from(900u64), U256::from(10_000u64 + 2 * 86400))
+    );
+    let err = c.execute_emergency_withdraw(token).unwrap_err();
+    match err {
+        RemittanceErrors::TimelockNotReady(_) => {}
+        _ => panic!("expected TimelockNotReady, got {:?}", err),
+    }
+
+    // Non-owners cannot queue or cancel
+    vm.set_sender(alice);
+    assert!(matches!(c.cancel_emergency_withdraw(token), Err(RemittanceErrors::Unauthorized(_))));
+
+    vm.set_sender(owner);
+    vm.set_block_timestamp(10_000 + 2 * 86400);
+    mock_transfer(&vm, token, treasury, U256::from(900u64));
+    c.execute_emergency_withdraw(token).unwrap();
+
+    // The event spells out the shortfall left behind
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = EmergencyWithdrawal::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.amount, U256::from(900u64));
+    assert_eq!(event.shortfall, U256::from(900u64));
+
+    // Nothing left to execute or cancel
+    assert!(matches!(c.execute_emergency_withdraw(token), Err(RemittanceErrors::NothingQueued(_))));
+    assert!(matches!(c.cancel_emergency_withdraw(token), Err(RemittanceErrors::NothingQueued(_))));
+}
+
+#[test]
+fn governance_changes_wait_for_timelock() {
+    use remittance_protocol::GovernanceChangeQueued;
+    use stylus_sdk::alloy_sol_types::SolEvent;
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+    assert_eq!(c.get_governance_delay(), U256::from(2 * 86400u64));
+
+    // Queueing a fee change leaves the current fee in place and emits the ETA
+    vm.set_block_timestamp(1_000);
+    let fee_change = c.update_platform_fee(U256::from(80u64)).unwrap();
+    let (topics, data) = vm.get_emitted_logs().last().unwrap().clone();
+    let event = GovernanceChangeQueued::decode_raw_log(topics, &data, true).unwrap();
+    assert_eq!(event.changeId, fee_change);
+    assert_eq!(event.eta, U256::from(1_000u64 + 2 * 86400));
+    let (_, _, fee_bps, _, _) = c.get_contract_stats();
+    assert_eq!(fee_bps, U256::from(50u64));
+
+    let new_treasury = address!("0x3000000000000000000000000000000000000003");
+    let treasury_change = c.update_treasury(new_treasury).unwrap();
+    let usdc = address!("af88d065e77c8cC2239327C5EDb3A432268e5831");
+    let token_change = c.remove_supported_token(usdc).unwrap();
+
+    let pending = c.get_pending_governance_changes();
+    assert_eq!(pending.len(), 3);
+    assert_eq!(pending[0], (fee_change, U256::ZERO, U256::from(80u64), Address::ZERO, U256::from(1_000u64 + 2 * 86400)));
+    assert_eq!(pending[1].3, new_treasury);
+    assert_eq!(pending[2].3, usdc);
+
+    // Too early
+    let err = c.execute_governance_change(fee_change).unwrap_err();
+    match err {
+        RemittanceErrors::TimelockNotReady(_) => {}
+        _ => panic!("expected TimelockNotReady, got {:?}", err),
+    }
+
+    // Only the matching role can execute or cancel
+    let stranger = address!("0xDEAD000000000000000000000000000000000000");
+    vm.set_sender(stranger);
+    assert!(matches!(c.cancel_governance_change(treasury_change), Err(RemittanceErrors::Unauthorized(_))));
+
+    vm.set_sender(owner);
+    c.cancel_governance_change(treasury_change).unwrap();
+    vm.set_block_timestamp(1_000 + 2 * 86400);
+    c.execute_governance_change(fee_change).unwrap();
+    c.execute_governance_change(token_change).unwrap();
+
+    let (_, _, fee_bps, _, tre) = c.get_contract_stats();
+    assert_eq!(fee_bps, U256::from(80u64));
+    assert_eq!(tre, treasury);
+    assert!(!c.is_token_supported(usdc));
+    assert!(c.get_pending_governance_changes().is_empty());
+
+    // Settled and unknown ids cannot be replayed
+    assert!(matches!(c.execute_governance_change(fee_change), Err(RemittanceErrors::NothingQueued(_))));
+    assert!(matches!(c.execute_governance_change(treasury_change), Err(RemittanceErrors::NothingQueued(_))));
+    assert!(matches!(c.cancel_governance_change(U256::from(99u64)), Err(RemittanceErrors::NothingQueued(_))));
+
+    // The delay itself is owner-governed and capped
+    let err = c.update_governance_delay(U256::from(31 * 86400u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+    let delay_change = c.update_governance_delay(U256::from(86400u64)).unwrap();
+    vm.set_block_timestamp(1_000 + 4 * 86400);
+    c.execute_governance_change(delay_change).unwrap();
+    assert_eq!(c.get_governance_delay(), U256::from(86400u64));
+}
+
+#[test]
+fn roles_gate_admin_functions() {
+    use remittance_protocol::{COMPLIANCE_ROLE, FEE_MANAGER_ROLE, PAUSER_ROLE};
+
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let pauser = address!("0x9A05E00000000000000000000000000000000000");
+    let officer = address!("0xC0391A0000000000000000000000000000000000");
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+
+    // The deployer starts with every role
+    assert!(c.has_role(PAUSER_ROLE, owner));
+    assert!(c.has_role(FEE_MANAGER_ROLE, owner));
+    assert!(!c.has_role(PAUSER_ROLE, pauser));
+
+    // Only the owner hands out roles
+    vm.set_sender(pauser);
+    assert!(matches!(c.grant_role(PAUSER_ROLE, pauser), Err(RemittanceErrors::Unauthorized(_))));
+    vm.set_sender(owner);
+    c.grant_role(PAUSER_ROLE, pauser).unwrap();
+    c.grant_role(COMPLIANCE_ROLE, officer).unwrap();
+    assert!(c.has_role(PAUSER_ROLE, pauser));
+
+    // Unknown role ids are rejected
+    let err = c.grant_role(Default::default(), pauser).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::InvalidConfiguration(_)));
+
+    // A role only unlocks its own functions
+    vm.set_sender(pauser);
+    c.pause().unwrap();
+    c.unpause().unwrap();
+    assert!(matches!(c.update_platform_fee(U256::from(10u64)), Err(RemittanceErrors::Unauthorized(_))));
+
+    // Revoked and renounced roles stop working
+    vm.set_sender(owner);
+    c.revoke_role(PAUSER_ROLE, pauser).unwrap();
+    vm.set_sender(pauser);
+    assert!(matches!(c.pause(), Err(RemittanceErrors::Unauthorized(_))));
+    vm.set_sender(owner);
+    c.renounce_role(FEE_MANAGER_ROLE).unwrap();
+    assert!(!c.has_role(FEE_MANAGER_ROLE, owner));
+    assert!(matches!(c.update_platform_fee(U256::from(10u64)), Err(RemittanceErrors::Unauthorized(_))));
+
+    // Compliance can suspend and reinstate a user
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+    vm.set_sender(officer);
+    c.set_user_active(alice, false).unwrap();
+    vm.set_sender(alice);
+    let err = c.deposit_balance(address!("af88d065e77c8cC2239327C5EDb3A432268e5831"), U256::from(1u64)).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::AccountSuspended(_)));
+    vm.set_sender(officer);
+    c.set_user_active(alice, true).unwrap();
+    let (_, _, _, is_active, _, _, _) = c.get_user_profile(alice);
+    assert!(is_active);
+}
+
+#[test]
+fn suspended_users_schedules_do_not_execute() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let keeper = address!("0x4EE9000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    mock_transfer(&vm, token, bob, U256::from(50u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+    vm.set_block_timestamp(1_000);
+    c.add_beneficiary(bob, "Bob".into(), "family".into(), U256::from(50u64), token, U256::from(1u64)).unwrap();
+    c.set_payment_schedule(U256::ZERO, U256::ZERO, U256::from(10_000u64), U256::ZERO, U256::ZERO).unwrap();
+    assert_eq!(c.get_due_queue_length(), U256::from(1u64));
+
+    // Suspension takes the schedule out of the queue and blocks direct execution
+    vm.set_sender(owner);
+    c.set_user_active(alice, false).unwrap();
+    assert_eq!(c.get_due_queue_length(), U256::ZERO);
+    vm.set_block_timestamp(20_000);
+    vm.set_sender(keeper);
+    let err = c.execute_auto_payments(alice, U256::ZERO).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::AccountSuspended(_)));
+    assert_eq!(c.execute_due(U256::from(10u64)).unwrap(), U256::ZERO);
+    assert_eq!(c.get_user_balance(alice, token), U256::from(1_000u64));
+
+    // Reinstating the user puts it back
+    vm.set_sender(owner);
+    c.set_user_active(alice, true).unwrap();
+    assert_eq!(c.peek_due(U256::from(10u64)), vec![(alice, U256::ZERO, U256::from(10_000u64))]);
+    vm.set_sender(keeper);
+    assert_eq!(c.execute_due(U256::from(10u64)).unwrap(), U256::from(1u64));
+}
+
+#[test]
+fn suspended_sender_scheduled_payment_does_not_execute() {
+    let vm = TestVM::default();
+    let mut c = UniversalRemittance::from(&vm);
+
+    let owner = address!("0x1000000000000000000000000000000000000001");
+    let treasury = address!("0x2000000000000000000000000000000000000002");
+    vm.set_sender(owner);
+    c.constructor(owner, treasury).unwrap();
+
+    let alice = address!("0xA11CE00000000000000000000000000000000000");
+    let bob = address!("0xB0B0000000000000000000000000000000000000");
+    let keeper = address!("0x4EE9000000000000000000000000000000000000");
+    vm.set_sender(alice);
+    c.register_user("Alice".into(), "NG".into(), "0803".into()).unwrap();
+
+    let token = address!("0xDDD0000000000000000000000000000000000000");
+    vm.set_sender(owner);
+    c.add_supported_token(token).unwrap();
+
+    let contract_addr = c.vm().contract_address();
+    mock_transfer_from(&vm, token, alice, contract_addr, U256::from(1_000u64));
+    vm.set_sender(alice);
+    c.deposit_balance(token, U256::from(1_000u64)).unwrap();
+    vm.set_block_timestamp(1_000);
+    let payment_id = c.schedule_payment(bob, U256::from(400u64), token, U256::from(5_000u64), "".into()).unwrap();
+
+    // A suspended sender can neither schedule new payments nor have a pending one run
+    vm.set_sender(owner);
+    c.set_user_active(alice, false).unwrap();
+    vm.set_sender(alice);
+    let err = c.schedule_payment(bob, U256::from(400u64), token, U256::from(5_000u64), "".into()).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::AccountSuspended(_)));
+
+    vm.set_block_timestamp(5_000);
+    mock_transfer(&vm, token, bob, U256::from(398u64));
+    mock_transfer(&vm, token, treasury, U256::from(2u64));
+    vm.set_sender(keeper);
+    let err = c.execute_scheduled_payment(payment_id).unwrap_err();
+    assert!(matches!(err, RemittanceErrors::AccountSuspended(_)));
+    let (_, _, _, _, _, _, _, completed) = c.get_payment(payment_id).unwrap();
+    assert!(!completed);
+
+    // Once reinstated it goes through
+    vm.set_sender(owner);
+    c.set_user_active(alice, true).unwrap();
+    vm.set_sender(keeper);
+    c.execute_scheduled_payment(payment_id).unwrap();
 }
 
 #[test]
@@ -656,7 +2605,7 @@ fn pause_blocks_mutations() {
 
     let owner = address!("0x1000000000000000000000000000000000000001");
     vm.set_sender(owner);
-    c.constructor(address!("0x2000000000000000000000000000000000000002")).unwrap();
+    c.constructor(owner, address!("0x2000000000000000000000000000000000000002")).unwrap();
 
     let alice = address!("0xA11CE00000000000000000000000000000000000");
     vm.set_sender(alice);